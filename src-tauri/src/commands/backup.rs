@@ -0,0 +1,825 @@
+use crate::commands::{clients, invoices, projects, sessions, settings, tags, tasks};
+use crate::database::migrations;
+use crate::models::{Client, Invoice, InvoiceItem, Project, SessionPause, Tag, Task, TimeSession};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// Bump when the JSON layout changes in a way older readers cannot handle
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Automatic backups: hours between runs (0 disables them) and how many to keep
+pub const AUTO_BACKUP_INTERVAL_HOURS: &str = "auto_backup_interval_hours";
+pub const AUTO_BACKUP_RETENTION: &str = "auto_backup_retention";
+const DEFAULT_INTERVAL_HOURS: i64 = 24;
+const DEFAULT_RETENTION: usize = 14;
+const AUTO_BACKUP_PREFIX: &str = "auto-";
+
+/// Everything needed to rebuild the database
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FullBackup {
+    pub format_version: u32,
+    pub schema_version: i32,
+    pub created_at: DateTime<Utc>,
+    pub clients: Vec<Client>,
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    pub sessions: Vec<TimeSession>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub session_tags: Vec<SessionTagLink>,
+    #[serde(default)]
+    pub session_pauses: Vec<SessionPause>,
+    #[serde(default)]
+    pub invoices: Vec<Invoice>,
+    #[serde(default)]
+    pub invoice_items: Vec<InvoiceItem>,
+    #[serde(default)]
+    pub settings: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionTagLink {
+    pub session_id: String,
+    pub tag_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityCounts {
+    pub total: usize,
+    pub new: usize,      // ids not present in the database
+    pub existing: usize, // ids already present (updated on merge only if newer)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupPreview {
+    pub format_version: u32,
+    pub schema_version: i32,
+    pub created_at: DateTime<Utc>,
+    pub clients: EntityCounts,
+    pub projects: EntityCounts,
+    pub tasks: EntityCounts,
+    pub sessions: EntityCounts,
+    pub tags: EntityCounts,
+    pub invoices: EntityCounts,
+    pub settings: usize,
+    // Numbers of backup invoices already used by a different invoice; a merge skips them
+    pub invoice_number_conflicts: Vec<String>,
+}
+
+pub(crate) fn collect_backup(conn: &Connection) -> Result<FullBackup, String> {
+    let (invoices, invoice_items) = invoices::query_all_invoices(conn)?;
+
+    let settings = conn
+        .prepare("SELECT key, value FROM settings")
+        .map_err(|e| e.to_string())?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<String, String>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(FullBackup {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version: migrations::schema_version(conn).map_err(|e| e.to_string())?,
+        created_at: Utc::now(),
+        clients: clients::query_all_clients(conn)?,
+        projects: projects::query_all_projects(conn)?,
+        tasks: tasks::query_all_tasks(conn)?,
+        sessions: sessions::query_all_sessions(conn)?,
+        tags: tags::query_all_tags(conn)?,
+        session_tags: tags::query_all_session_tags(conn)?
+            .into_iter()
+            .map(|(session_id, tag_id)| SessionTagLink { session_id, tag_id })
+            .collect(),
+        session_pauses: sessions::query_session_pauses(conn, None)?,
+        invoices,
+        invoice_items,
+        settings,
+    })
+}
+
+pub(crate) fn write_backup_file(conn: &Connection, file_path: &Path) -> Result<FullBackup, String> {
+    let backup = collect_backup(conn)?;
+    let json = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
+
+    // Write next to the target and rename so a crash never leaves half a backup
+    let tmp_path = file_path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp_path, file_path).map_err(|e| e.to_string())?;
+
+    Ok(backup)
+}
+
+fn read_backup_file(file_path: &str) -> Result<FullBackup, String> {
+    let json = std::fs::read_to_string(file_path).map_err(|e| e.to_string())?;
+    let backup: FullBackup =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid backup file: {}", e))?;
+    validate_backup(&backup)?;
+    Ok(backup)
+}
+
+fn validate_backup(backup: &FullBackup) -> Result<(), String> {
+    if backup.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format v{} is newer than this app supports",
+            backup.format_version
+        ));
+    }
+    if backup.schema_version > migrations::LATEST_VERSION {
+        return Err(format!(
+            "Backup was made with a newer database schema (v{})",
+            backup.schema_version
+        ));
+    }
+
+    let client_ids: Vec<&str> = backup.clients.iter().map(|c| c.id.as_str()).collect();
+    let project_ids: Vec<&str> = backup.projects.iter().map(|p| p.id.as_str()).collect();
+    let invoice_ids: Vec<&str> = backup.invoices.iter().map(|i| i.id.as_str()).collect();
+    let task_ids: Vec<&str> = backup.tasks.iter().map(|t| t.id.as_str()).collect();
+
+    if let Some(project) = backup
+        .projects
+        .iter()
+        .find(|p| p.client_id.as_deref().is_some_and(|id| !client_ids.contains(&id)))
+    {
+        return Err(format!("Project \"{}\" references a client missing from the backup", project.name));
+    }
+    if let Some(session) = backup
+        .sessions
+        .iter()
+        .find(|s| !project_ids.contains(&s.project_id.as_str()))
+    {
+        return Err(format!("Session {} references a project missing from the backup", session.id));
+    }
+    if let Some(task) = backup
+        .tasks
+        .iter()
+        .find(|t| !project_ids.contains(&t.project_id.as_str()))
+    {
+        return Err(format!("Task \"{}\" references a project missing from the backup", task.name));
+    }
+    if let Some(session) = backup
+        .sessions
+        .iter()
+        .find(|s| s.task_id.as_deref().is_some_and(|id| !task_ids.contains(&id)))
+    {
+        return Err(format!("Session {} references a task missing from the backup", session.id));
+    }
+    if let Some(session) = backup
+        .sessions
+        .iter()
+        .find(|s| s.invoice_id.as_deref().is_some_and(|id| !invoice_ids.contains(&id)))
+    {
+        return Err(format!("Session {} references an invoice missing from the backup", session.id));
+    }
+    let session_ids: Vec<&str> = backup.sessions.iter().map(|s| s.id.as_str()).collect();
+    let tag_ids: Vec<&str> = backup.tags.iter().map(|t| t.id.as_str()).collect();
+    if backup
+        .session_tags
+        .iter()
+        .any(|link| !session_ids.contains(&link.session_id.as_str()) || !tag_ids.contains(&link.tag_id.as_str()))
+    {
+        return Err("A session tag references a session or tag missing from the backup".to_string());
+    }
+    if let Some(pause) = backup
+        .session_pauses
+        .iter()
+        .find(|p| !session_ids.contains(&p.session_id.as_str()))
+    {
+        return Err(format!("Pause {} references a session missing from the backup", pause.id));
+    }
+    if let Some(session) = backup
+        .sessions
+        .iter()
+        .find(|s| s.end_time.is_some_and(|end| end < s.start_time))
+    {
+        return Err(format!("Session {} ends before it starts", session.id));
+    }
+
+    Ok(())
+}
+
+fn count_entities<'a>(
+    conn: &Connection,
+    table: &str,
+    ids: impl Iterator<Item = &'a str>,
+) -> Result<EntityCounts, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT 1 FROM {} WHERE id = ?1", table))
+        .map_err(|e| e.to_string())?;
+
+    let mut counts = EntityCounts { total: 0, new: 0, existing: 0 };
+    for id in ids {
+        counts.total += 1;
+        let exists = stmt
+            .query_row([id], |_| Ok(()))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if exists {
+            counts.existing += 1;
+        } else {
+            counts.new += 1;
+        }
+    }
+
+    Ok(counts)
+}
+
+/// Backup invoices whose number is taken by a different invoice in the database
+fn conflicting_invoices<'a>(conn: &Connection, backup: &'a FullBackup) -> Result<Vec<&'a Invoice>, String> {
+    let mut stmt = conn
+        .prepare("SELECT 1 FROM invoices WHERE number = ?1 AND id != ?2")
+        .map_err(|e| e.to_string())?;
+
+    let mut conflicts = Vec::new();
+    for invoice in &backup.invoices {
+        let taken = stmt
+            .query_row([&invoice.number, &invoice.id], |_| Ok(()))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if taken {
+            conflicts.push(invoice);
+        }
+    }
+
+    Ok(conflicts)
+}
+
+fn preview(conn: &Connection, backup: &FullBackup) -> Result<BackupPreview, String> {
+    Ok(BackupPreview {
+        format_version: backup.format_version,
+        schema_version: backup.schema_version,
+        created_at: backup.created_at,
+        clients: count_entities(conn, "clients", backup.clients.iter().map(|c| c.id.as_str()))?,
+        projects: count_entities(conn, "projects", backup.projects.iter().map(|p| p.id.as_str()))?,
+        tasks: count_entities(conn, "tasks", backup.tasks.iter().map(|t| t.id.as_str()))?,
+        sessions: count_entities(conn, "time_sessions", backup.sessions.iter().map(|s| s.id.as_str()))?,
+        tags: count_entities(conn, "tags", backup.tags.iter().map(|t| t.id.as_str()))?,
+        invoices: count_entities(conn, "invoices", backup.invoices.iter().map(|i| i.id.as_str()))?,
+        settings: backup.settings.len(),
+        invoice_number_conflicts: conflicting_invoices(conn, backup)?
+            .into_iter()
+            .map(|invoice| invoice.number.clone())
+            .collect(),
+    })
+}
+
+/// Insert the backup rows. On conflict, a row is only overwritten when the
+/// backup copy is newer (`updated_at`); invoices and settings are never overwritten.
+/// An invoice whose number belongs to another invoice is skipped along with its
+/// items, and its sessions come in uninvoiced.
+fn import_backup(conn: &Connection, backup: &FullBackup) -> Result<(), String> {
+    let now = Utc::now();
+    let skipped_invoices: HashSet<&str> = conflicting_invoices(conn, backup)?
+        .into_iter()
+        .map(|invoice| invoice.id.as_str())
+        .collect();
+
+    for client in &backup.clients {
+        conn.execute(
+            "INSERT INTO clients (id, name, description, color, hourly_rate, currency, billing_address, tax_id,
+             created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, description = excluded.description,
+             color = excluded.color, hourly_rate = excluded.hourly_rate, currency = excluded.currency,
+             billing_address = excluded.billing_address, tax_id = excluded.tax_id, updated_at = excluded.updated_at
+             WHERE excluded.updated_at > clients.updated_at",
+            rusqlite::params![
+                &client.id,
+                &client.name,
+                &client.description,
+                &client.color,
+                &client.hourly_rate,
+                &client.currency,
+                &client.billing_address,
+                &client.tax_id,
+                &client.created_at.to_rfc3339(),
+                &client.updated_at.to_rfc3339()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for project in &backup.projects {
+        conn.execute(
+            "INSERT INTO projects (id, name, description, client_id, color, priority, status, estimated_hours,
+             hours_per_day, hours_per_week, deadline, hourly_rate, currency, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, description = excluded.description,
+             client_id = excluded.client_id, color = excluded.color, priority = excluded.priority,
+             status = excluded.status, estimated_hours = excluded.estimated_hours,
+             hours_per_day = excluded.hours_per_day, hours_per_week = excluded.hours_per_week,
+             deadline = excluded.deadline, hourly_rate = excluded.hourly_rate, currency = excluded.currency,
+             updated_at = excluded.updated_at
+             WHERE excluded.updated_at > projects.updated_at",
+            rusqlite::params![
+                &project.id,
+                &project.name,
+                &project.description,
+                &project.client_id,
+                &project.color,
+                &project.priority,
+                &project.status,
+                &project.estimated_hours,
+                &project.hours_per_day,
+                &project.hours_per_week,
+                &project.deadline.map(|d| d.to_rfc3339()),
+                &project.hourly_rate,
+                &project.currency,
+                &project.created_at.to_rfc3339(),
+                &project.updated_at.to_rfc3339()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for task in &backup.tasks {
+        conn.execute(
+            "INSERT INTO tasks (id, project_id, name, description, status, estimated_hours, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(id) DO UPDATE SET project_id = excluded.project_id, name = excluded.name,
+             description = excluded.description, status = excluded.status,
+             estimated_hours = excluded.estimated_hours, updated_at = excluded.updated_at
+             WHERE excluded.updated_at > tasks.updated_at",
+            rusqlite::params![
+                &task.id,
+                &task.project_id,
+                &task.name,
+                &task.description,
+                &task.status,
+                &task.estimated_hours,
+                &task.created_at.to_rfc3339(),
+                &task.updated_at.to_rfc3339()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for invoice in backup.invoices.iter().filter(|i| !skipped_invoices.contains(i.id.as_str())) {
+        conn.execute(
+            "INSERT INTO invoices (id, number, client_id, period_start, period_end, issue_date, currency,
+             subtotal, tax_rate, tax_amount, total, notes, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT(id) DO NOTHING",
+            rusqlite::params![
+                &invoice.id,
+                &invoice.number,
+                &invoice.client_id,
+                &invoice.period_start,
+                &invoice.period_end,
+                &invoice.issue_date,
+                &invoice.currency,
+                &invoice.subtotal,
+                &invoice.tax_rate,
+                &invoice.tax_amount,
+                &invoice.total,
+                &invoice.notes,
+                &invoice.created_at.to_rfc3339()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for item in backup
+        .invoice_items
+        .iter()
+        .filter(|i| !skipped_invoices.contains(i.invoice_id.as_str()))
+    {
+        conn.execute(
+            "INSERT INTO invoice_items (id, invoice_id, project_id, description, hours, rate, amount)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO NOTHING",
+            rusqlite::params![
+                &item.id,
+                &item.invoice_id,
+                &item.project_id,
+                &item.description,
+                &item.hours,
+                &item.rate,
+                &item.amount
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for session in &backup.sessions {
+        conn.execute(
+            "INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, notes, is_running,
+             billable, invoice_id, created_at, updated_at, task_id, timezone, pomodoros, last_heartbeat)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+             ON CONFLICT(id) DO UPDATE SET project_id = excluded.project_id, start_time = excluded.start_time,
+             end_time = excluded.end_time, duration_seconds = excluded.duration_seconds, notes = excluded.notes,
+             is_running = excluded.is_running, billable = excluded.billable, invoice_id = excluded.invoice_id,
+             task_id = excluded.task_id, timezone = excluded.timezone, pomodoros = excluded.pomodoros,
+             last_heartbeat = excluded.last_heartbeat, updated_at = excluded.updated_at
+             WHERE excluded.updated_at > time_sessions.updated_at",
+            rusqlite::params![
+                &session.id,
+                &session.project_id,
+                &session.start_time.to_rfc3339(),
+                &session.end_time.map(|e| e.to_rfc3339()),
+                &session.duration_seconds,
+                &session.notes,
+                &session.is_running,
+                &session.billable,
+                &session.invoice_id.as_deref().filter(|id| !skipped_invoices.contains(id)),
+                &session.created_at.to_rfc3339(),
+                &session.updated_at.to_rfc3339(),
+                &session.task_id,
+                &session.timezone,
+                &session.pomodoros,
+                // Backups carry no heartbeat; a restored running session counts as alive now
+                &session.is_running.then(|| now.to_rfc3339())
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    // Tag names are unique: a backup tag whose name already exists under another
+    // id is folded into the existing tag
+    let mut tag_remap: HashMap<&str, String> = HashMap::new();
+    for tag in &backup.tags {
+        let same_name: Option<String> = conn
+            .query_row(
+                "SELECT id FROM tags WHERE name = ?1 AND id != ?2",
+                [&tag.name, &tag.id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(existing_id) = same_name {
+            tag_remap.insert(&tag.id, existing_id);
+            continue;
+        }
+
+        conn.execute(
+            "INSERT INTO tags (id, name, color, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, color = excluded.color,
+             updated_at = excluded.updated_at
+             WHERE excluded.updated_at > tags.updated_at",
+            rusqlite::params![
+                &tag.id,
+                &tag.name,
+                &tag.color,
+                &tag.created_at.to_rfc3339(),
+                &tag.updated_at.to_rfc3339()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for link in &backup.session_tags {
+        let tag_id = tag_remap.get(link.tag_id.as_str()).unwrap_or(&link.tag_id);
+        conn.execute(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag_id) VALUES (?1, ?2)",
+            [&link.session_id, tag_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for pause in &backup.session_pauses {
+        conn.execute(
+            "INSERT INTO session_pauses (id, session_id, paused_at, resumed_at, reason) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(id) DO UPDATE SET resumed_at = excluded.resumed_at",
+            rusqlite::params![
+                &pause.id,
+                &pause.session_id,
+                &pause.paused_at.to_rfc3339(),
+                &pause.resumed_at.map(|r| r.to_rfc3339()),
+                &pause.reason
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for (key, value) in &backup.settings {
+        conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3) ON CONFLICT(key) DO NOTHING",
+            rusqlite::params![key, value, &now.to_rfc3339()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn backups_dir<R: Runtime>(app: &AppHandle<R>) -> Result<std::path::PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("backups");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Write a full backup (all clients, projects, sessions, invoices and settings)
+#[tauri::command]
+pub fn create_full_backup(
+    db: State<Mutex<Connection>>,
+    file_path: String,
+) -> Result<BackupPreview, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let backup = write_backup_file(&conn, Path::new(&file_path))?;
+    preview(&conn, &backup)
+}
+
+/// Validate a backup file and report what restoring it would change
+#[tauri::command]
+pub fn preview_backup(
+    db: State<Mutex<Connection>>,
+    file_path: String,
+) -> Result<BackupPreview, String> {
+    let backup = read_backup_file(&file_path)?;
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    preview(&conn, &backup)
+}
+
+/// Restore a backup in one transaction. `mode` is "merge" (keep current data,
+/// add what is missing, take newer copies) or "replace" (wipe and load the backup).
+/// The current data is saved to the backups folder first, and restored running
+/// sessions the session policy does not allow are stopped.
+#[tauri::command]
+pub fn restore_backup<R: Runtime>(
+    app: AppHandle<R>,
+    db: State<Mutex<Connection>>,
+    file_path: String,
+    mode: String,
+) -> Result<BackupPreview, String> {
+    let backup = read_backup_file(&file_path)?;
+    if mode != "merge" && mode != "replace" {
+        return Err(format!("Unknown restore mode: {}", mode));
+    }
+
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let summary = preview(&conn, &backup)?;
+
+    let safety_copy = backups_dir(&app)?.join(format!(
+        "pre-restore-{}.json",
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    write_backup_file(&conn, &safety_copy)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    apply_restore(&tx, &backup, &mode)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(summary)
+}
+
+/// The database side of `restore_backup`; run it inside a transaction
+fn apply_restore(conn: &Connection, backup: &FullBackup, mode: &str) -> Result<(), String> {
+    if mode == "replace" {
+        conn.execute_batch(
+            "DELETE FROM invoice_items;
+             DELETE FROM session_tags;
+             DELETE FROM session_pauses;
+             DELETE FROM tags;
+             DELETE FROM time_sessions;
+             DELETE FROM tasks;
+             DELETE FROM invoices;
+             DELETE FROM projects;
+             DELETE FROM clients;
+             DELETE FROM settings;
+             DELETE FROM budget_alerts;
+             DELETE FROM target_alerts;
+             DELETE FROM operation_journal;",
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let running_before: HashSet<String> = running_session_ids(conn)?.into_iter().collect();
+    import_backup(conn, backup)?;
+    enforce_session_policy(conn, &running_before)
+}
+
+fn running_session_ids(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM time_sessions WHERE is_running = 1 ORDER BY start_time DESC")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+/// Sessions the restore set running must still fit the session policy. Those that
+/// were already running keep going, then the newest restored ones are let in; the
+/// rest are stopped at their last update.
+fn enforce_session_policy(conn: &Connection, running_before: &HashSet<String>) -> Result<(), String> {
+    let restored: Vec<String> = running_session_ids(conn)?
+        .into_iter()
+        .filter(|id| !running_before.contains(id))
+        .collect();
+    for id in &restored {
+        conn.execute("UPDATE time_sessions SET is_running = 0 WHERE id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    for id in &restored {
+        let session = sessions::fetch_session(conn, id)?;
+        if sessions::ensure_can_start(conn, &session.project_id).is_ok() {
+            conn.execute("UPDATE time_sessions SET is_running = 1 WHERE id = ?1", [id])
+                .map_err(|e| e.to_string())?;
+        } else {
+            log::info!("Stopping restored session {} to respect the session policy", id);
+            sessions::close_session_at(conn, &session, session.updated_at)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write an automatic backup when the newest one is older than the configured
+/// interval, then delete the oldest ones beyond the retention count.
+pub(crate) fn run_scheduled_backup<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let db = app.state::<Mutex<Connection>>();
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let interval_hours = settings::read_setting(&conn, AUTO_BACKUP_INTERVAL_HOURS)?
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_INTERVAL_HOURS);
+    let retention = settings::read_setting(&conn, AUTO_BACKUP_RETENTION)?
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_RETENTION)
+        .max(1);
+
+    if interval_hours <= 0 {
+        return Ok(());
+    }
+
+    let dir = backups_dir(app)?;
+    let mut existing: Vec<std::path::PathBuf> = std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(AUTO_BACKUP_PREFIX) && n.ends_with(".json"))
+        })
+        .collect();
+    // Timestamped names sort chronologically
+    existing.sort();
+
+    let newest_age_hours = existing
+        .last()
+        .and_then(|path| path.metadata().ok()?.modified().ok())
+        .and_then(|modified| modified.elapsed().ok())
+        .map(|age| age.as_secs() as i64 / 3600);
+
+    if newest_age_hours.map_or(true, |age| age >= interval_hours) {
+        let path = dir.join(format!(
+            "{}{}.json",
+            AUTO_BACKUP_PREFIX,
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        write_backup_file(&conn, &path)?;
+        log::info!("Automatic backup written to {}", path.display());
+        existing.push(path);
+    }
+
+    while existing.len() > retention {
+        let oldest = existing.remove(0);
+        std::fs::remove_file(&oldest).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Check for a due automatic backup at startup and then every hour
+pub fn setup_automatic_backups<R: Runtime>(app: &tauri::App<R>) {
+    let app_handle = app.handle().clone();
+
+    std::thread::spawn(move || loop {
+        if let Err(e) = run_scheduled_backup(&app_handle) {
+            eprintln!("Automatic backup failed: {}", e);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(3600));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    const T0: &str = "2024-01-15T09:00:00+00:00";
+
+    /// A client with one invoiced, finished session and one running session
+    fn seed(conn: &Connection, prefix: &str, invoice_number: &str) {
+        conn.execute_batch(&format!(
+            "INSERT INTO clients (id, name, created_at, updated_at) VALUES ('{p}c', 'Client {p}', '{t}', '{t}');
+             INSERT INTO projects (id, name, client_id, created_at, updated_at)
+             VALUES ('{p}p', 'Project {p}', '{p}c', '{t}', '{t}');
+             INSERT INTO invoices (id, number, client_id, period_start, period_end, issue_date, subtotal,
+             tax_amount, total, created_at)
+             VALUES ('{p}i', '{n}', '{p}c', '2024-01-01', '2024-01-31', '2024-02-01', 100, 0, 100, '{t}');
+             INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, invoice_id,
+             created_at, updated_at)
+             VALUES ('{p}s1', '{p}p', '{t}', '2024-01-15T10:00:00+00:00', 3600, '{p}i', '{t}', '{t}');
+             INSERT INTO time_sessions (id, project_id, start_time, is_running, created_at, updated_at)
+             VALUES ('{p}s2', '{p}p', '{r}', 1, '{r}', '{r}');",
+            p = prefix,
+            t = T0,
+            r = "2024-01-16T09:00:00+00:00",
+            n = invoice_number
+        ))
+        .unwrap();
+    }
+
+    fn ids(conn: &Connection, table: &str) -> Vec<String> {
+        conn.prepare(&format!("SELECT id FROM {} ORDER BY id", table))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn preview_counts_new_and_existing_rows() {
+        let source = test_connection();
+        seed(&source, "a", "INV-1");
+        let backup = collect_backup(&source).unwrap();
+
+        let summary = preview(&test_connection(), &backup).unwrap();
+        assert_eq!((summary.sessions.total, summary.sessions.new), (2, 2));
+        assert!(summary.invoice_number_conflicts.is_empty());
+
+        let summary = preview(&source, &backup).unwrap();
+        assert_eq!((summary.sessions.new, summary.sessions.existing), (0, 2));
+        assert_eq!(summary.invoices.existing, 1);
+    }
+
+    #[test]
+    fn replace_restores_exactly_the_backup() {
+        let source = test_connection();
+        seed(&source, "a", "INV-1");
+        let backup = collect_backup(&source).unwrap();
+
+        let target = test_connection();
+        seed(&target, "b", "INV-1");
+        apply_restore(&target, &backup, "replace").unwrap();
+
+        assert_eq!(ids(&target, "time_sessions"), vec!["as1", "as2"]);
+        assert_eq!(ids(&target, "invoices"), vec!["ai"]);
+        let heartbeat: Option<String> = target
+            .query_row("SELECT last_heartbeat FROM time_sessions WHERE id = 'as2'", [], |row| row.get(0))
+            .unwrap();
+        assert!(heartbeat.is_some());
+        assert_eq!(collect_backup(&target).unwrap().sessions.len(), backup.sessions.len());
+    }
+
+    #[test]
+    fn merge_keeps_current_rows_and_skips_taken_invoice_numbers() {
+        let source = test_connection();
+        seed(&source, "a", "INV-1");
+        let backup = collect_backup(&source).unwrap();
+
+        let target = test_connection();
+        seed(&target, "b", "INV-1");
+        let summary = preview(&target, &backup).unwrap();
+        assert_eq!(summary.invoice_number_conflicts, vec!["INV-1".to_string()]);
+
+        apply_restore(&target, &backup, "merge").unwrap();
+
+        assert_eq!(ids(&target, "time_sessions"), vec!["as1", "as2", "bs1", "bs2"]);
+        assert_eq!(ids(&target, "invoices"), vec!["bi"]);
+        let invoice_id: Option<String> = target
+            .query_row("SELECT invoice_id FROM time_sessions WHERE id = 'as1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(invoice_id, None);
+    }
+
+    #[test]
+    fn merge_stops_restored_sessions_the_policy_does_not_allow() {
+        let source = test_connection();
+        seed(&source, "a", "INV-1");
+        let backup = collect_backup(&source).unwrap();
+
+        let target = test_connection();
+        seed(&target, "b", "INV-2");
+        settings::write_setting(&target, settings::SESSION_POLICY, "one_global").unwrap();
+        apply_restore(&target, &backup, "merge").unwrap();
+
+        let running = sessions::query_all_sessions(&target)
+            .unwrap()
+            .into_iter()
+            .filter(|s| s.is_running)
+            .map(|s| s.id)
+            .collect::<Vec<_>>();
+        assert_eq!(running, vec!["bs2".to_string()]);
+        let stopped = sessions::fetch_session(&target, "as2").unwrap();
+        assert!(stopped.end_time.is_some());
+    }
+}
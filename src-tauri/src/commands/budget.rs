@@ -0,0 +1,243 @@
+use crate::commands::{projects, stats};
+use crate::models::{BudgetStatus, BurnDownPoint, Project};
+use crate::timezone;
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+
+/// Days of history the velocity is averaged over
+const VELOCITY_WINDOW_DAYS: i64 = 14;
+/// Percentages of the estimate that trigger a notification
+const ALERT_THRESHOLDS: [i64; 3] = [75, 90, 100];
+/// Seconds between background budget checks
+const CHECK_INTERVAL_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Serialize)]
+struct BudgetAlert {
+    project_id: String,
+    project_name: String,
+    threshold: i64,
+    percent_used: f64,
+}
+
+fn find_project(conn: &Connection, project_id: &str) -> Result<Project, String> {
+    projects::query_all_projects(conn)?
+        .into_iter()
+        .find(|p| p.id == project_id)
+        .ok_or_else(|| format!("Project not found: {}", project_id))
+}
+
+fn has_estimate(project: &Project) -> bool {
+    project.estimated_hours.is_some_and(|hours| hours > 0.0)
+}
+
+/// All-time seconds logged on a project, including the running sessions so far
+fn consumed_seconds(conn: &Connection, project_id: &str) -> Result<i64, String> {
+    let closed: i64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(duration_seconds), 0) FROM time_sessions
+             WHERE project_id = ?1 AND is_running = 0",
+            [project_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let running: i64 = stats::running_elapsed(conn, Utc::now())?
+        .into_iter()
+        .filter(|(id, _)| id == project_id)
+        .map(|(_, seconds)| seconds)
+        .sum();
+
+    Ok(closed + running)
+}
+
+fn budget_status(conn: &Connection, project: &Project) -> Result<BudgetStatus, String> {
+    let tz = timezone::user_timezone(conn);
+    let today = timezone::local_date(Utc::now(), tz);
+
+    let consumed_seconds = consumed_seconds(conn, &project.id)?;
+    let consumed_hours = consumed_seconds as f64 / 3600.0;
+
+    // Velocity: average hours per calendar day over the recent window, idle days included
+    let window_start = today - Duration::days(VELOCITY_WINDOW_DAYS - 1);
+    let recent_seconds: i64 = stats::project_daily_seconds(conn, &project.id, window_start, today)?
+        .values()
+        .sum();
+    let velocity = recent_seconds as f64 / 3600.0 / VELOCITY_WINDOW_DAYS as f64;
+
+    let estimate = project.estimated_hours.filter(|hours| *hours > 0.0);
+    let remaining = estimate.map(|estimate| (estimate - consumed_hours).max(0.0));
+    let projected = remaining
+        .filter(|remaining| *remaining > 0.0 && velocity > 0.0)
+        .map(|remaining| today + Duration::days((remaining / velocity).ceil() as i64));
+
+    Ok(BudgetStatus {
+        project_id: project.id.clone(),
+        project_name: project.name.clone(),
+        estimated_hours: estimate,
+        consumed_seconds,
+        consumed_hours,
+        remaining_hours: remaining,
+        percent_used: estimate.map(|estimate| (consumed_hours / estimate * 1000.0).round() / 10.0),
+        velocity_hours_per_day: velocity,
+        projected_completion_date: projected.map(|date| date.format("%Y-%m-%d").to_string()),
+        deadline: project.deadline,
+        on_track: projected
+            .zip(project.deadline)
+            .map(|(projected, deadline)| projected <= timezone::local_date(deadline, tz)),
+    })
+}
+
+/// Consumed vs. estimated time for a project, with a completion date projected
+/// from the last two weeks' pace
+#[tauri::command]
+pub fn get_budget_status(db: State<Mutex<Connection>>, project_id: String) -> Result<BudgetStatus, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let project = find_project(&conn, &project_id)?;
+    budget_status(&conn, &project)
+}
+
+/// Budget status of every non-archived project that has an estimate
+#[tauri::command]
+pub fn get_all_budget_statuses(db: State<Mutex<Connection>>) -> Result<Vec<BudgetStatus>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    projects::query_all_projects(&conn)?
+        .iter()
+        .filter(|p| has_estimate(p) && p.status != "archived")
+        .map(|p| budget_status(&conn, p))
+        .collect()
+}
+
+/// Consumed and remaining hours at the end of each day in the range (up to today)
+#[tauri::command]
+pub fn get_budget_burndown(
+    db: State<Mutex<Connection>>,
+    project_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<BurnDownPoint>, String> {
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", value))
+    };
+    let (start, end) = (parse(&start_date)?, parse(&end_date)?);
+
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let project = find_project(&conn, &project_id)?;
+    let today = timezone::local_date(Utc::now(), timezone::user_timezone(&conn));
+    let end = end.min(today);
+    if start > end {
+        return Ok(Vec::new());
+    }
+
+    // Walk back from today's total: each day's figure excludes what came after it
+    let consumed_now = consumed_seconds(&conn, &project.id)?;
+    let daily = stats::project_daily_seconds(&conn, &project.id, start, today)?;
+    let estimate = project.estimated_hours.filter(|hours| *hours > 0.0);
+
+    let mut points = Vec::new();
+    let mut date = start;
+    while date <= end {
+        let logged_after: i64 = daily.range(date.succ_opt().unwrap()..).map(|(_, seconds)| seconds).sum();
+        let consumed_hours = (consumed_now - logged_after) as f64 / 3600.0;
+        points.push(BurnDownPoint {
+            date: date.format("%Y-%m-%d").to_string(),
+            consumed_hours,
+            remaining_hours: estimate.map(|estimate| (estimate - consumed_hours).max(0.0)),
+        });
+        date = date.succ_opt().unwrap();
+    }
+
+    Ok(points)
+}
+
+/// Notify once per threshold as a project's logged time crosses 75/90/100 % of its
+/// estimate. Thresholds the project falls back under (e.g. after the estimate is
+/// raised) are re-armed.
+pub(crate) fn check_budget_alerts<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let db = app.state::<Mutex<Connection>>();
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now().to_rfc3339();
+
+    for project in projects::query_all_projects(&conn)?
+        .iter()
+        .filter(|p| has_estimate(p) && p.status != "archived")
+    {
+        let status = budget_status(&conn, project)?;
+        let percent = status.percent_used.unwrap_or(0.0);
+
+        // Only the highest newly crossed threshold is announced
+        let mut crossed = None;
+        for threshold in ALERT_THRESHOLDS {
+            if percent >= threshold as f64 {
+                let inserted = conn
+                    .execute(
+                        "INSERT OR IGNORE INTO budget_alerts (project_id, threshold, notified_at) VALUES (?1, ?2, ?3)",
+                        rusqlite::params![&project.id, threshold, &now],
+                    )
+                    .map_err(|e| e.to_string())?;
+                if inserted == 1 {
+                    crossed = Some(threshold);
+                }
+            } else {
+                conn.execute(
+                    "DELETE FROM budget_alerts WHERE project_id = ?1 AND threshold = ?2",
+                    rusqlite::params![&project.id, threshold],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+
+        let Some(threshold) = crossed else { continue };
+        let estimate = status.estimated_hours.unwrap_or(0.0);
+        let body = if threshold >= 100 {
+            format!(
+                "Se alcanzaron las {:.1} h estimadas ({:.1} h registradas)",
+                estimate, status.consumed_hours
+            )
+        } else {
+            format!(
+                "Se ha usado el {}% de las {:.1} h estimadas ({:.1} h registradas)",
+                threshold, estimate, status.consumed_hours
+            )
+        };
+
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(format!("Presupuesto de {}", project.name))
+            .body(body)
+            .show()
+        {
+            eprintln!("Failed to show budget notification: {}", e);
+        }
+
+        let _ = app.emit(
+            "budget-alert",
+            BudgetAlert {
+                project_id: project.id.clone(),
+                project_name: project.name.clone(),
+                threshold,
+                percent_used: percent,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Check project budgets at startup and then every few minutes, so running
+/// timers trigger alerts as they cross a threshold
+pub fn setup_budget_alerts<R: Runtime>(app: &tauri::App<R>) {
+    let app_handle = app.handle().clone();
+
+    std::thread::spawn(move || loop {
+        if let Err(e) = check_budget_alerts(&app_handle) {
+            eprintln!("Budget check failed: {}", e);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+    });
+}
@@ -1,6 +1,7 @@
+use crate::commands::journal::{self, TableImage};
 use crate::models::Client;
 use chrono::Utc;
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 use std::sync::Mutex;
 use tauri::State;
 use uuid::Uuid;
@@ -8,9 +9,12 @@ use uuid::Uuid;
 #[tauri::command]
 pub fn get_all_clients(db: State<Mutex<Connection>>) -> Result<Vec<Client>, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
+    query_all_clients(&conn)
+}
 
+pub(crate) fn query_all_clients(conn: &Connection) -> Result<Vec<Client>, String> {
     let mut stmt = conn
-        .prepare("SELECT id, name, description, color, created_at, updated_at FROM clients ORDER BY name ASC")
+        .prepare("SELECT id, name, description, color, hourly_rate, currency, billing_address, tax_id, created_at, updated_at FROM clients ORDER BY name ASC")
         .map_err(|e| e.to_string())?;
 
     let clients = stmt
@@ -20,8 +24,12 @@ pub fn get_all_clients(db: State<Mutex<Connection>>) -> Result<Vec<Client>, Stri
                 name: row.get(1)?,
                 description: row.get(2)?,
                 color: row.get(3)?,
-                created_at: row.get::<_, String>(4)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(5)?.parse().unwrap(),
+                hourly_rate: row.get(4)?,
+                currency: row.get(5)?,
+                billing_address: row.get(6)?,
+                tax_id: row.get(7)?,
+                created_at: row.get::<_, String>(8)?.parse().unwrap(),
+                updated_at: row.get::<_, String>(9)?.parse().unwrap(),
             })
         })
         .map_err(|e| e.to_string())?
@@ -37,7 +45,7 @@ pub fn get_client(db: State<Mutex<Connection>>, id: String) -> Result<Client, St
 
     let client = conn
         .query_row(
-            "SELECT id, name, description, color, created_at, updated_at FROM clients WHERE id = ?1",
+            "SELECT id, name, description, color, hourly_rate, currency, billing_address, tax_id, created_at, updated_at FROM clients WHERE id = ?1",
             [&id],
             |row| {
                 Ok(Client {
@@ -45,8 +53,12 @@ pub fn get_client(db: State<Mutex<Connection>>, id: String) -> Result<Client, St
                     name: row.get(1)?,
                     description: row.get(2)?,
                     color: row.get(3)?,
-                    created_at: row.get::<_, String>(4)?.parse().unwrap(),
-                    updated_at: row.get::<_, String>(5)?.parse().unwrap(),
+                    hourly_rate: row.get(4)?,
+                    currency: row.get(5)?,
+                    billing_address: row.get(6)?,
+                    tax_id: row.get(7)?,
+                    created_at: row.get::<_, String>(8)?.parse().unwrap(),
+                    updated_at: row.get::<_, String>(9)?.parse().unwrap(),
                 })
             },
         )
@@ -77,6 +89,10 @@ pub fn create_client(
         name,
         description,
         color,
+        hourly_rate: None,
+        currency: None,
+        billing_address: None,
+        tax_id: None,
         created_at: now,
         updated_at: now,
     })
@@ -103,12 +119,76 @@ pub fn update_client(
     get_client(db, id)
 }
 
+/// Set the default hourly rate and currency billed to a client, plus the
+/// address and tax id printed on its invoices
 #[tauri::command]
-pub fn delete_client(db: State<Mutex<Connection>>, id: String) -> Result<(), String> {
+pub fn update_client_billing(
+    db: State<Mutex<Connection>>,
+    id: String,
+    hourly_rate: Option<f64>,
+    currency: Option<String>,
+    billing_address: Option<String>,
+    tax_id: Option<String>,
+) -> Result<Client, String> {
+    if hourly_rate.is_some_and(|rate| rate < 0.0) {
+        return Err("Hourly rate cannot be negative".to_string());
+    }
+
     let conn = db.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    conn.execute(
+        "UPDATE clients SET hourly_rate = ?1, currency = ?2, billing_address = ?3, tax_id = ?4,
+         updated_at = ?5 WHERE id = ?6",
+        rusqlite::params![
+            &hourly_rate,
+            &currency.map(|c| c.to_uppercase()),
+            &billing_address,
+            &tax_id,
+            &now.to_rfc3339(),
+            &id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    drop(conn);
+    get_client(db, id)
+}
+
+/// Move a client to the trash; its projects stay, without a client. Clients with
+/// invoices are kept until those are voided. See `journal` for undo and restore.
+#[tauri::command]
+pub fn delete_client(db: State<Mutex<Connection>>, id: String) -> Result<(), String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
 
-    conn.execute("DELETE FROM clients WHERE id = ?1", [&id])
+    journal::perform(&tx, "delete_client", &id)?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Delete a client and unlink its projects, returning its name and the before-images
+/// of the client and those projects
+pub(crate) fn remove_client(conn: &Connection, id: &str) -> Result<(String, Vec<TableImage>), String> {
+    let name: String = conn
+        .query_row("SELECT name FROM clients WHERE id = ?1", [id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Client not found: {}", id))?;
+
+    let invoices: i64 = conn
+        .query_row("SELECT COUNT(*) FROM invoices WHERE client_id = ?1", [id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if invoices > 0 {
+        return Err("The client has invoices; void them first".to_string());
+    }
+
+    let images = vec![
+        journal::capture(conn, "projects", "client_id = ?1", id)?,
+        journal::remove_rows(conn, "clients", "id = ?1", id)?,
+    ];
+    conn.execute("UPDATE projects SET client_id = NULL WHERE client_id = ?1", [id])
         .map_err(|e| e.to_string())?;
 
-    Ok(())
+    Ok((name, images))
 }
@@ -1,4 +1,4 @@
-use crate::commands::{projects, sessions, stats};
+use crate::commands::{projects, sessions, stats, tags};
 use crate::models::{DailyStats, Project, TimeSession};
 use rusqlite::Connection;
 use std::sync::Mutex;
@@ -6,9 +6,11 @@ use tauri::State;
 use chrono::{NaiveDate, Datelike};
 use std::collections::HashMap;
 
-use printpdf::*;
-use std::fs::File;
-use std::io::BufWriter;
+use crate::pdf_layout::{FontStyle, PdfLayout, MARGIN_LEFT, MARGIN_RIGHT};
+use crate::timezone;
+use chrono_tz::Tz;
+use printpdf::{Color, Rgb};
+use rust_xlsxwriter::{Format, Workbook};
 
 fn format_duration_from_seconds(seconds: i64) -> String {
     let hours = seconds / 3600;
@@ -41,16 +43,18 @@ pub fn export_daily_backup(
 ) -> Result<String, String> {
     let all_sessions = sessions::get_all_sessions(db.clone())?;
     let all_projects = projects::get_all_projects(db.clone())?;
+    let user_tz = timezone::user_timezone(&*db.lock().map_err(|e| e.to_string())?);
 
-    // Filter sessions for the specific date
+    // Filter sessions for the specific date, as seen where each session was recorded
     let day_sessions: Vec<TimeSession> = all_sessions
         .into_iter()
         .filter(|s| {
-            s.start_time.format("%Y-%m-%d").to_string() == date
+            let tz = timezone::session_timezone(s.timezone.as_deref(), user_tz);
+            timezone::local_date(s.start_time, tz).format("%Y-%m-%d").to_string() == date
         })
         .collect();
 
-    let stats = stats::get_daily_stats(db, date.clone(), date.clone())?;
+    let stats = stats::get_daily_stats(db, date.clone(), date.clone(), None, None)?;
 
     let backup = DailyBackup {
         date,
@@ -83,26 +87,46 @@ pub fn generate_pdf_report(
     file_path: String,
 ) -> Result<(), String> {
     // Get data
-    let daily_stats = stats::get_daily_stats(db.clone(), start_date.clone(), end_date.clone())?;
+    let daily_stats = stats::get_daily_stats(db.clone(), start_date.clone(), end_date.clone(), None, None)?;
     let all_sessions = sessions::get_all_sessions(db.clone())?;
+    let task_breakdown = stats::get_task_breakdown(db.clone(), start_date.clone(), end_date.clone(), None)?;
+    let db_conn = db.clone();
     let all_projects = projects::get_all_projects(db)?;
 
-    // Filter sessions for date range
-    let range_sessions: Vec<TimeSession> = all_sessions
-        .into_iter()
-        .filter(|s| {
-            let session_date = s.start_time.format("%Y-%m-%d").to_string();
-            session_date >= start_date && session_date <= end_date && !s.is_running
-        })
+    let task_names: HashMap<&str, &str> = task_breakdown
+        .iter()
+        .filter_map(|t| Some((t.task_id.as_deref()?, t.task_name.as_deref()?)))
         .collect();
 
-    // Create PDF document (A4 size)
-    let (doc, page1, layer1) = PdfDocument::new("Reporte de Tiempo", Mm(210.0), Mm(297.0), "Capa 1");
-    let current_layer = doc.get_page(page1).get_layer(layer1);
+    let (user_tz, pauses) = {
+        let conn = db_conn.lock().map_err(|e| e.to_string())?;
+        (timezone::user_timezone(&conn), sessions::pauses_by_session(&conn)?)
+    };
+    let local_start = |s: &TimeSession| {
+        s.start_time
+            .with_timezone(&timezone::session_timezone(s.timezone.as_deref(), user_tz))
+    };
 
-    // Fonts
-    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
-    let font_regular = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+    // Split finished sessions at local midnights and keep the days within the range
+    let mut sessions_by_date: HashMap<String, Vec<(&TimeSession, stats::DayPortion)>> = HashMap::new();
+    for session in all_sessions.iter().filter(|s| !s.is_running) {
+        let Some(end_time) = session.end_time else { continue };
+        let tz = local_start(session).timezone();
+        let session_pauses = pauses.get(&session.id).map_or(&[][..], Vec::as_slice);
+        let portions =
+            stats::day_portions(session.start_time, end_time, session.duration_seconds.unwrap_or(0), tz, session_pauses);
+        for portion in portions {
+            let date = portion.date.format("%Y-%m-%d").to_string();
+            if date >= start_date && date <= end_date {
+                sessions_by_date.entry(date).or_default().push((session, portion));
+            }
+        }
+    }
+
+    let mut layout = PdfLayout::new(
+        "Reporte de Tiempo",
+        &format!("Reporte de Tiempo · {} – {}", start_date, end_date),
+    )?;
 
     // Colors
     let blue = Color::Rgb(Rgb::new(41.0/255.0, 128.0/255.0, 185.0/255.0, None));
@@ -110,52 +134,64 @@ pub fn generate_pdf_report(
     let green = Color::Rgb(Rgb::new(39.0/255.0, 174.0/255.0, 96.0/255.0, None));
     let light_gray = Color::Rgb(Rgb::new(0.85, 0.85, 0.85, None));
 
-    let mut y_position = 270.0; // Start from top
-
     // Title
-    current_layer.set_fill_color(blue.clone());
-    current_layer.use_text("══ REPORTE DE TIEMPO ══", 26.0, Mm(20.0), Mm(y_position), &font_bold);
-    y_position -= 10.0;
+    layout.text("══ REPORTE DE TIEMPO ══", 26.0, MARGIN_LEFT, 0.0, FontStyle::Bold, &blue);
+    layout.advance(10.0);
 
     // Date range
-    current_layer.set_fill_color(dark_gray.clone());
     let range_text = format!("Período: {} hasta {}", start_date, end_date);
-    current_layer.use_text(&range_text, 13.0, Mm(20.0), Mm(y_position), &font_regular);
-    y_position -= 15.0;
+    layout.text(&range_text, 13.0, MARGIN_LEFT, 0.0, FontStyle::Regular, &dark_gray);
+    layout.advance(15.0);
 
     // Total summary
-    let total_seconds: i64 = daily_stats.iter()
-        .map(|s| (s.total_hours * 3600.0) as i64)
-        .sum();
+    let total_seconds: i64 = daily_stats.iter().map(|s| s.total_seconds).sum();
 
-    current_layer.set_fill_color(dark_gray.clone());
-    current_layer.use_text("TIEMPO TOTAL:", 14.0, Mm(25.0), Mm(y_position), &font_bold);
-    current_layer.set_fill_color(green.clone());
+    layout.text("TIEMPO TOTAL:", 14.0, 25.0, 0.0, FontStyle::Bold, &dark_gray);
     let total_text = format_duration_from_seconds(total_seconds);
-    current_layer.use_text(&total_text, 20.0, Mm(85.0), Mm(y_position - 1.0), &font_bold);
-
-    current_layer.set_fill_color(dark_gray.clone());
-    y_position -= 15.0;
+    layout.text(&total_text, 20.0, 85.0, -1.0, FontStyle::Bold, &green);
+    layout.advance(15.0);
 
     // Horizontal line
-    let line_y = y_position + 2.0;
-    let line_points = Line {
-        points: vec![
-            (Point::new(Mm(20.0), Mm(line_y)), false),
-            (Point::new(Mm(190.0), Mm(line_y)), false),
-        ],
-        is_closed: false,
-    };
-    current_layer.set_outline_color(blue.clone());
-    current_layer.set_outline_thickness(1.0);
-    current_layer.add_line(line_points);
-    y_position -= 5.0;
-
-    // Group sessions by date
-    let mut sessions_by_date: HashMap<String, Vec<&TimeSession>> = HashMap::new();
-    for session in &range_sessions {
-        let date = session.start_time.format("%Y-%m-%d").to_string();
-        sessions_by_date.entry(date).or_insert_with(Vec::new).push(session);
+    layout.rule(2.0, MARGIN_LEFT, MARGIN_RIGHT, &blue, 1.0);
+    layout.advance(5.0);
+
+    // Time per task, grouped by project (only when tasks are in use)
+    if task_breakdown.iter().any(|t| t.task_id.is_some()) {
+        layout.ensure_space(25.0);
+        layout.advance(8.0);
+        layout.text("Desglose por tarea", 14.0, 25.0, 0.0, FontStyle::Bold, &blue);
+        layout.advance(9.0);
+
+        let mut current_project: Option<&str> = None;
+        for row in &task_breakdown {
+            if current_project != Some(row.project_id.as_str()) {
+                layout.ensure_space(12.0);
+                layout.advance(1.0);
+                layout.text(&row.project_name, 11.0, 28.0, 0.0, FontStyle::Bold, &dark_gray);
+                layout.advance(5.5);
+                current_project = Some(row.project_id.as_str());
+            }
+
+            layout.ensure_space(5.0);
+            let task_name = row.task_name.as_deref().unwrap_or("Sin tarea");
+            let task_line = layout
+                .wrap(task_name, 10.0, FontStyle::Regular, 95.0)
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            layout.text(&task_line, 10.0, 33.0, 0.0, FontStyle::Regular, &dark_gray);
+
+            if let Some(estimated) = row.estimated_hours {
+                let estimate_text = format!("{:.1}h / {:.1}h est.", row.total_hours, estimated);
+                layout.text_right(&estimate_text, 9.0, 150.0, 0.0, FontStyle::Regular, &dark_gray);
+            }
+            let duration_text = format_duration_from_seconds(row.total_seconds);
+            layout.text_right(&duration_text, 10.0, 185.0, 0.0, FontStyle::Bold, &green);
+            layout.advance(5.0);
+        }
+
+        layout.rule(0.0, MARGIN_LEFT, MARGIN_RIGHT, &blue, 1.0);
+        layout.advance(5.0);
     }
 
     // Sort dates
@@ -164,124 +200,315 @@ pub fn generate_pdf_report(
 
     // Print each day
     for date in dates {
-        if y_position < 30.0 {
-            // Need new page
-            let (_new_page, _new_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Capa");
-            y_position = 270.0;
-        }
-
-        y_position -= 8.0;
+        // Keep the day header together with its first session
+        layout.ensure_space(25.0);
+        layout.advance(8.0);
 
         // Calculate day total
         let day_total_seconds: i64 = sessions_by_date.get(&date)
             .map(|sessions| sessions.iter()
-                .map(|s| s.duration_seconds.unwrap_or(0) as i64)
+                .map(|(_, portion)| portion.seconds)
                 .sum())
             .unwrap_or(0);
 
         // Day header underline
-        let day_underline = Line {
-            points: vec![
-                (Point::new(Mm(20.0), Mm(y_position - 6.0)), false),
-                (Point::new(Mm(190.0), Mm(y_position - 6.0)), false),
-            ],
-            is_closed: false,
-        };
-        current_layer.set_outline_color(light_gray.clone());
-        current_layer.set_outline_thickness(0.5);
-        current_layer.add_line(day_underline);
+        layout.rule(-6.0, MARGIN_LEFT, MARGIN_RIGHT, &light_gray, 0.5);
 
         // Day date
-        current_layer.set_fill_color(blue.clone());
         let date_formatted = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
             .map(|d| d.format("%A, %d de %B de %Y").to_string())
             .unwrap_or(date.clone());
-        current_layer.use_text(&date_formatted, 14.0, Mm(25.0), Mm(y_position - 2.0), &font_bold);
+        layout.text(&date_formatted, 14.0, 25.0, -2.0, FontStyle::Bold, &blue);
 
         // Day total (right aligned)
-        current_layer.set_fill_color(green.clone());
         let day_total_text = format_duration_from_seconds(day_total_seconds);
-        current_layer.use_text(&day_total_text, 12.0, Mm(160.0), Mm(y_position - 2.0), &font_bold);
+        layout.text_right(&day_total_text, 12.0, 185.0, -2.0, FontStyle::Bold, &green);
 
-        current_layer.set_fill_color(dark_gray.clone());
-        y_position -= 10.0;
+        layout.advance(10.0);
 
         if let Some(sessions) = sessions_by_date.get(&date) {
-            for session in sessions {
-                if y_position < 30.0 {
-                    let (_new_page, _new_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Capa");
-                    y_position = 270.0;
-                }
+            for (session, portion) in sessions {
+                layout.ensure_space(8.0);
 
                 let project = all_projects.iter().find(|p| p.id == session.project_id);
                 let project_name = project.map(|p| p.name.as_str()).unwrap_or("Desconocido");
+                let project_label = match session.task_id.as_deref().and_then(|id| task_names.get(id)) {
+                    Some(task_name) => format!("{} · {}", project_name, task_name),
+                    None => project_name.to_string(),
+                };
 
-                let duration_seconds = session.duration_seconds.unwrap_or(0) as i64;
-                let duration_formatted = format_duration_from_seconds(duration_seconds);
-                let start_time = session.start_time.format("%H:%M").to_string();
-                let end_time = session.end_time.as_ref()
-                    .map(|et| et.format("%H:%M").to_string())
-                    .unwrap_or_else(|| "-".to_string());
+                // Only the part of the session that falls on this day
+                let duration_formatted = format_duration_from_seconds(portion.seconds);
+                let tz = local_start(session).timezone();
+                let start_time = portion.start.with_timezone(&tz).format("%H:%M").to_string();
+                let end_time = portion.end.with_timezone(&tz).format("%H:%M").to_string();
 
                 // Session bullet point
-                current_layer.set_fill_color(blue.clone());
-                current_layer.use_text("•", 14.0, Mm(28.0), Mm(y_position), &font_bold);
+                layout.text("•", 14.0, 28.0, 0.0, FontStyle::Bold, &blue);
 
                 // Time range
-                current_layer.set_fill_color(dark_gray.clone());
                 let time_text = format!("{} - {}", start_time, end_time);
-                current_layer.use_text(&time_text, 11.0, Mm(33.0), Mm(y_position), &font_regular);
+                layout.text(&time_text, 11.0, 33.0, 0.0, FontStyle::Regular, &dark_gray);
 
-                // Project name
-                current_layer.use_text(project_name, 11.0, Mm(60.0), Mm(y_position), &font_bold);
+                // Project name, cut to the space left of the duration column
+                let project_line = layout
+                    .wrap(&project_label, 11.0, FontStyle::Bold, 95.0)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                layout.text(&project_line, 11.0, 60.0, 0.0, FontStyle::Bold, &dark_gray);
 
                 // Duration (right aligned)
-                current_layer.set_fill_color(green.clone());
-                current_layer.use_text(&duration_formatted, 11.0, Mm(160.0), Mm(y_position), &font_bold);
+                layout.text_right(&duration_formatted, 11.0, 185.0, 0.0, FontStyle::Bold, &green);
 
-                current_layer.set_fill_color(dark_gray.clone());
-                y_position -= 5.0;
+                layout.advance(5.0);
 
                 if let Some(notes) = &session.notes {
                     if !notes.is_empty() {
-                        let notes_text = if notes.len() > 80 {
-                            format!("  📝 {}...", &notes[..77])
-                        } else {
-                            format!("  📝 {}", notes)
-                        };
-                        current_layer.use_text(&notes_text, 9.0, Mm(33.0), Mm(y_position), &font_regular);
-                        y_position -= 5.0;
+                        let notes_text = format!("📝 {}", notes);
+                        layout.paragraph(&notes_text, 9.0, 35.0, 150.0, 4.5, FontStyle::Regular, &dark_gray);
+                        layout.advance(0.5);
                     }
                 }
 
                 // Separator line
-                let sep_line = Line {
-                    points: vec![
-                        (Point::new(Mm(28.0), Mm(y_position + 1.0)), false),
-                        (Point::new(Mm(185.0), Mm(y_position + 1.0)), false),
-                    ],
-                    is_closed: false,
-                };
-                current_layer.set_outline_color(light_gray.clone());
-                current_layer.set_outline_thickness(0.2);
-                current_layer.add_line(sep_line);
+                layout.rule(1.0, 28.0, 185.0, &light_gray, 0.2);
 
-                y_position -= 2.0;
+                layout.advance(2.0);
             }
         }
     }
 
-    // Save PDF
-    doc.save(&mut BufWriter::new(File::create(&file_path).map_err(|e| e.to_string())?))
+    layout.save(&file_path)
+}
+
+const EXPORT_COLUMNS: &[&str] = &[
+    "date", "start", "end", "duration_hours", "project", "client", "notes", "billable", "tags",
+];
+
+fn export_column_label(column: &str) -> &'static str {
+    match column {
+        "date" => "Fecha",
+        "start" => "Inicio",
+        "end" => "Fin",
+        "duration_hours" => "Duración (h)",
+        "project" => "Proyecto",
+        "client" => "Cliente",
+        "notes" => "Notas",
+        "billable" => "Facturable",
+        "tags" => "Etiquetas",
+        _ => "",
+    }
+}
+
+/// Locales that write decimals with a comma (and therefore separate CSV fields with ';')
+fn uses_decimal_comma(locale: &str) -> bool {
+    const COMMA_LANGUAGES: &[&str] = &[
+        "es", "fr", "de", "it", "pt", "nl", "ca", "gl", "eu", "da", "fi", "nb", "sv", "pl", "cs", "ru", "tr",
+    ];
+    let language = locale.split(['-', '_']).next().unwrap_or("").to_lowercase();
+    COMMA_LANGUAGES.contains(&language.as_str())
+}
+
+enum ExportValue {
+    Text(String),
+    Hours(f64),
+}
+
+struct ExportRow {
+    session: TimeSession,
+    project_name: String,
+    client_name: Option<String>,
+    tags: Option<String>, // comma-separated tag names
+    tz: Tz,               // zone the session was recorded in; dates and times are shown in it
+}
+
+impl ExportRow {
+    fn value(&self, column: &str) -> ExportValue {
+        let session = &self.session;
+        let text = match column {
+            "date" => session.start_time.with_timezone(&self.tz).format("%Y-%m-%d").to_string(),
+            "start" => session.start_time.with_timezone(&self.tz).format("%H:%M").to_string(),
+            "end" => session
+                .end_time
+                .map(|e| e.with_timezone(&self.tz).format("%H:%M").to_string())
+                .unwrap_or_default(),
+            "duration_hours" => {
+                let hours = session.duration_seconds.unwrap_or(0) as f64 / 3600.0;
+                return ExportValue::Hours((hours * 100.0).round() / 100.0);
+            }
+            "project" => self.project_name.clone(),
+            "client" => self.client_name.clone().unwrap_or_default(),
+            "notes" => session.notes.clone().unwrap_or_default(),
+            "billable" => if session.billable { "Sí" } else { "No" }.to_string(),
+            "tags" => self.tags.clone().unwrap_or_default(),
+            _ => String::new(),
+        };
+        ExportValue::Text(text)
+    }
+}
+
+fn query_export_rows(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    client_id: &Option<String>,
+    project_id: &Option<String>,
+    tag_ids: &Option<Vec<String>>,
+) -> Result<Vec<ExportRow>, String> {
+    // The session columns come first so `session_from_row` can read them in place
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT ts.*, p.name, c.name,
+                (SELECT GROUP_CONCAT(t.name, ', ') FROM session_tags st JOIN tags t ON st.tag_id = t.id
+                 WHERE st.session_id = ts.id) as tags
+             FROM (SELECT {} FROM time_sessions
+                   WHERE is_running = 0
+                   AND local_date(start_time, timezone) >= ?1
+                   AND local_date(start_time, timezone) <= ?2) ts
+             JOIN projects p ON ts.project_id = p.id
+             LEFT JOIN clients c ON p.client_id = c.id
+             WHERE (?3 IS NULL OR p.client_id = ?3)
+             AND (?4 IS NULL OR ts.project_id = ?4)
+             AND {}
+             ORDER BY ts.start_time ASC",
+            sessions::SESSION_COLUMNS,
+            tags::tag_filter_clause(5)
+        ))
         .map_err(|e| e.to_string())?;
 
-    Ok(())
+    let user_tz = timezone::user_timezone(conn);
+    let tag_filter = tags::tag_filter_param(tag_ids);
+    let rows = stmt
+        .query_map(rusqlite::params![start_date, end_date, client_id, project_id, tag_filter], |row| {
+            let session = sessions::session_from_row(row)?;
+            let tz = timezone::session_timezone(session.timezone.as_deref(), user_tz);
+            Ok(ExportRow {
+                session,
+                project_name: row.get(16)?,
+                client_name: row.get(17)?,
+                tags: row.get(18)?,
+                tz,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+fn write_sessions_csv(
+    rows: &[ExportRow],
+    columns: &[String],
+    decimal_comma: bool,
+    file_path: &str,
+) -> Result<(), String> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(if decimal_comma { b';' } else { b',' })
+        .from_path(file_path)
+        .map_err(|e| e.to_string())?;
+
+    writer
+        .write_record(columns.iter().map(|c| export_column_label(c)))
+        .map_err(|e| e.to_string())?;
+
+    for row in rows {
+        let record: Vec<String> = columns
+            .iter()
+            .map(|column| match row.value(column) {
+                ExportValue::Text(text) => text,
+                ExportValue::Hours(hours) if decimal_comma => format!("{:.2}", hours).replace('.', ","),
+                ExportValue::Hours(hours) => format!("{:.2}", hours),
+            })
+            .collect();
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+    }
+
+    writer.flush().map_err(|e| e.to_string())
+}
+
+fn write_sessions_xlsx(rows: &[ExportRow], columns: &[String], file_path: &str) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Sesiones").map_err(|e| e.to_string())?;
+
+    // Numbers are stored as numbers; the spreadsheet renders them with the
+    // reader's own decimal separator
+    let header_format = Format::new().set_bold();
+    let hours_format = Format::new().set_num_format("0.00");
+
+    for (col, column) in columns.iter().enumerate() {
+        worksheet
+            .write_string_with_format(0, col as u16, export_column_label(column), &header_format)
+            .map_err(|e| e.to_string())?;
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let excel_row = index as u32 + 1;
+        for (col, column) in columns.iter().enumerate() {
+            match row.value(column) {
+                ExportValue::Text(text) => worksheet.write_string(excel_row, col as u16, text),
+                ExportValue::Hours(hours) => {
+                    worksheet.write_number_with_format(excel_row, col as u16, hours, &hours_format)
+                }
+            }
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    worksheet.autofit();
+    workbook.save(file_path).map_err(|e| e.to_string())
+}
+
+/// Export the sessions of a date range as a spreadsheet.
+/// `format` is "csv" or "xlsx"; `columns` picks and orders the columns (all by default);
+/// `locale` (e.g. "es-ES") decides the CSV decimal separator; `tag_ids` keeps only
+/// sessions carrying any of those tags.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn export_sessions(
+    db: State<Mutex<Connection>>,
+    start_date: String,
+    end_date: String,
+    file_path: String,
+    format: String,
+    columns: Option<Vec<String>>,
+    client_id: Option<String>,
+    project_id: Option<String>,
+    locale: Option<String>,
+    tag_ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let columns = columns.unwrap_or_else(|| EXPORT_COLUMNS.iter().map(|c| c.to_string()).collect());
+    if columns.is_empty() {
+        return Err("Select at least one column".to_string());
+    }
+    if let Some(unknown) = columns.iter().find(|c| !EXPORT_COLUMNS.contains(&c.as_str())) {
+        return Err(format!("Unknown export column: {}", unknown));
+    }
+
+    let rows = {
+        let conn = db.lock().map_err(|e| e.to_string())?;
+        query_export_rows(&conn, &start_date, &end_date, &client_id, &project_id, &tag_ids)?
+    };
+
+    match format.as_str() {
+        "csv" => {
+            let decimal_comma = locale.as_deref().is_some_and(uses_decimal_comma);
+            write_sessions_csv(&rows, &columns, decimal_comma, &file_path)?
+        }
+        "xlsx" => write_sessions_xlsx(&rows, &columns, &file_path)?,
+        other => return Err(format!("Unsupported export format: {}", other)),
+    }
+
+    Ok(rows.len())
 }
 
-/// Get current month date range
+/// Get current month date range in the user's time zone
 #[tauri::command]
-pub fn get_current_month_range() -> Result<(String, String), String> {
-    let now = chrono::Local::now();
+pub fn get_current_month_range(db: State<Mutex<Connection>>) -> Result<(String, String), String> {
+    let user_tz = timezone::user_timezone(&*db.lock().map_err(|e| e.to_string())?);
+    let now = chrono::Utc::now().with_timezone(&user_tz);
     let year = now.year();
     let month = now.month();
 
@@ -297,3 +524,39 @@ pub fn get_current_month_range() -> Result<(String, String), String> {
 
     Ok((first_day.format("%Y-%m-%d").to_string(), last_day.format("%Y-%m-%d").to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    #[test]
+    fn export_rows_carry_pauses_tags_and_names() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO clients (id, name, created_at, updated_at) VALUES ('c1', 'Client', '', '');
+             INSERT INTO projects (id, name, client_id, created_at, updated_at) VALUES ('p1', 'Project', 'c1', '', '');
+             INSERT INTO tags (id, name, created_at, updated_at) VALUES ('t1', 'alpha', '', ''), ('t2', 'beta', '', '');
+             INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, timezone,
+             created_at, updated_at)
+             VALUES ('s1', 'p1', '2024-01-15T09:00:00+00:00', '2024-01-15T11:00:00+00:00', 5400, 'UTC',
+             '2024-01-15T09:00:00+00:00', '2024-01-15T09:00:00+00:00');
+             INSERT INTO session_tags (session_id, tag_id) VALUES ('s1', 't1'), ('s1', 't2');
+             INSERT INTO session_pauses (id, session_id, paused_at, resumed_at)
+             VALUES ('pa1', 's1', '2024-01-15T10:00:00+00:00', '2024-01-15T10:30:00+00:00');",
+        )
+        .unwrap();
+
+        let rows = query_export_rows(&conn, "2024-01-15", "2024-01-15", &None, &None, &Some(vec!["t2".to_string()]))
+            .unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].session.id, "s1");
+        assert_eq!(rows[0].session.paused_seconds, 1800);
+        assert_eq!(rows[0].project_name, "Project");
+        assert_eq!(rows[0].client_name.as_deref(), Some("Client"));
+        let mut tags: Vec<&str> = rows[0].tags.as_deref().unwrap().split(", ").collect();
+        tags.sort();
+        assert_eq!(tags, vec!["alpha", "beta"]);
+    }
+}
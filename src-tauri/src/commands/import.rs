@@ -0,0 +1,730 @@
+use crate::timezone;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+use tauri::State;
+use uuid::Uuid;
+
+/// Project used for entries exported without one (Toggl allows that)
+const FALLBACK_PROJECT: &str = "Sin proyecto";
+/// Harvest only records hours per day; such entries are laid out back to back from here
+const DAY_START_HOUR: u32 = 9;
+
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d.%m.%Y"];
+/// Readings of slashed dates: US exports write 03/04/2024 for March 4, European
+/// ones for April 3. Which one a file uses is decided once for the whole file.
+const MONTH_FIRST: &str = "%m/%d/%Y";
+const DAY_FIRST: &str = "%d/%m/%Y";
+const TIME_FORMATS: &[&str] = &["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p", "%I:%M%p"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportIssue {
+    pub line: usize, // CSV line or JSON entry number, 1-based
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub source: String,
+    pub dry_run: bool,
+    pub entries_read: usize,
+    pub clients_created: Vec<String>,
+    pub clients_matched: Vec<String>,
+    pub projects_created: Vec<String>,
+    pub projects_matched: Vec<String>,
+    pub sessions_imported: usize,
+    pub imported_seconds: i64,
+    pub duplicates_skipped: usize,
+    pub conflicts: Vec<ImportIssue>,
+    pub errors: Vec<ImportIssue>,
+}
+
+/// One time entry from a foreign export, already normalized to UTC
+struct ImportedEntry {
+    line: usize,
+    client: Option<String>,
+    project: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    notes: Option<String>,
+    billable: bool,
+}
+
+/// Import time entries exported from Toggl, Clockify or Harvest (CSV or JSON).
+/// Clients and projects are matched by name (case-insensitive) and created when
+/// missing. With `dry_run` (the default) the import runs inside a transaction
+/// that is rolled back, so the report shows exactly what would be written.
+/// `date_format` ("month_first" or "day_first") says how to read dates such as
+/// 03/04/2024; when omitted it is worked out from the file, and the import fails
+/// if every date in it could be read either way.
+#[tauri::command]
+pub fn import_time_entries(
+    db: State<Mutex<Connection>>,
+    file_path: String,
+    source: String,
+    dry_run: Option<bool>,
+    date_format: Option<String>,
+) -> Result<ImportReport, String> {
+    if !matches!(source.as_str(), "toggl" | "clockify" | "harvest") {
+        return Err(format!("Unknown import source: {}", source));
+    }
+    let dry_run = dry_run.unwrap_or(true);
+    let slash_format = match date_format.as_deref() {
+        None => None,
+        Some("month_first") => Some(MONTH_FIRST),
+        Some("day_first") => Some(DAY_FIRST),
+        Some(other) => return Err(format!("Unknown date format: {}", other)),
+    };
+
+    let content = std::fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+    let content = content.trim_start_matches('\u{feff}');
+
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tz = timezone::user_timezone(&conn);
+
+    let is_json = file_path.to_lowercase().ends_with(".json");
+    let (entries, errors) = if is_json {
+        parse_json(content, &source, tz)?
+    } else {
+        parse_csv(content, tz, slash_format)?
+    };
+
+    let mut report = ImportReport {
+        source,
+        dry_run,
+        entries_read: entries.len() + errors.len(),
+        clients_created: Vec::new(),
+        clients_matched: Vec::new(),
+        projects_created: Vec::new(),
+        projects_matched: Vec::new(),
+        sessions_imported: 0,
+        imported_seconds: 0,
+        duplicates_skipped: 0,
+        conflicts: Vec::new(),
+        errors,
+    };
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    apply_import(&tx, &entries, tz, &mut report)?;
+
+    if dry_run {
+        tx.rollback().map_err(|e| e.to_string())?;
+    } else {
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(report)
+}
+
+fn apply_import(
+    conn: &Connection,
+    entries: &[ImportedEntry],
+    tz: Tz,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    let now = Utc::now().to_rfc3339();
+
+    let mut clients: HashMap<String, String> = conn
+        .prepare("SELECT name, id FROM clients")
+        .map_err(|e| e.to_string())?
+        .query_map([], |row| Ok((row.get::<_, String>(0)?.trim().to_lowercase(), row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut projects: HashMap<String, (String, Option<String>)> = conn
+        .prepare("SELECT name, id, client_id FROM projects")
+        .map_err(|e| e.to_string())?
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?.trim().to_lowercase(), (row.get(1)?, row.get(2)?)))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Created names by lowercased key, so a later row spelling one differently
+    // does not count it as matched as well
+    let mut clients_created = BTreeMap::new();
+    let mut clients_matched = BTreeSet::new();
+    let mut projects_created = BTreeMap::new();
+    let mut projects_matched = BTreeSet::new();
+    let mut reported_projects = BTreeSet::new();
+
+    for entry in entries {
+        let client_id = match entry.client.as_deref() {
+            Some(name) => {
+                let key = name.to_lowercase();
+                if let Some(id) = clients.get(&key) {
+                    if !clients_created.contains_key(&key) {
+                        clients_matched.insert(name.to_string());
+                    }
+                    Some(id.clone())
+                } else {
+                    let id = Uuid::new_v4().to_string();
+                    conn.execute(
+                        "INSERT INTO clients (id, name, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+                        rusqlite::params![&id, name, &now],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    clients_created.insert(key.clone(), name.to_string());
+                    clients.insert(key, id.clone());
+                    Some(id)
+                }
+            }
+            None => None,
+        };
+
+        let key = entry.project.to_lowercase();
+        let project_id = if let Some((id, existing_client)) = projects.get(&key) {
+            if !projects_created.contains_key(&key) {
+                projects_matched.insert(entry.project.clone());
+            }
+            if client_id.is_some() && *existing_client != client_id && reported_projects.insert(key.clone()) {
+                report.conflicts.push(ImportIssue {
+                    line: entry.line,
+                    message: format!(
+                        "Project \"{}\" already exists under another client; using the existing project",
+                        entry.project
+                    ),
+                });
+            }
+            id.clone()
+        } else {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO projects (id, name, client_id, priority, status, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, 2, 'active', ?4, ?4)",
+                rusqlite::params![&id, &entry.project, &client_id, &now],
+            )
+            .map_err(|e| e.to_string())?;
+            projects_created.insert(key.clone(), entry.project.clone());
+            projects.insert(key, (id.clone(), client_id.clone()));
+            id
+        };
+
+        let start = entry.start.to_rfc3339();
+        let end = entry.end.to_rfc3339();
+
+        // Same project, same span: already imported (or exported from here)
+        let duplicate = conn
+            .query_row(
+                "SELECT 1 FROM time_sessions WHERE project_id = ?1 AND start_time = ?2 AND end_time = ?3",
+                rusqlite::params![&project_id, &start, &end],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if duplicate {
+            report.duplicates_skipped += 1;
+            continue;
+        }
+
+        let overlapping: Option<String> = conn
+            .query_row(
+                "SELECT start_time FROM time_sessions
+                 WHERE project_id = ?1 AND start_time < ?3 AND COALESCE(end_time, ?3) > ?2
+                 LIMIT 1",
+                rusqlite::params![&project_id, &start, &end],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(existing_start) = overlapping {
+            report.conflicts.push(ImportIssue {
+                line: entry.line,
+                message: format!(
+                    "Overlaps an existing session of \"{}\" started at {}; not imported",
+                    entry.project, existing_start
+                ),
+            });
+            continue;
+        }
+
+        let duration = (entry.end - entry.start).num_seconds();
+        conn.execute(
+            "INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, notes, is_running,
+             billable, timezone, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?8, ?9, ?9)",
+            rusqlite::params![
+                &Uuid::new_v4().to_string(),
+                &project_id,
+                &start,
+                &end,
+                &duration,
+                &entry.notes,
+                &entry.billable,
+                tz.name(),
+                &now
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        report.sessions_imported += 1;
+        report.imported_seconds += duration;
+    }
+
+    report.clients_created = clients_created.into_values().collect();
+    report.clients_matched = clients_matched.into_iter().collect();
+    report.projects_created = projects_created.into_values().collect();
+    report.projects_matched = projects_matched.into_iter().collect();
+
+    Ok(())
+}
+
+/// CSV exports of all three tools share enough header names to read them the
+/// same way; Harvest has no start/end times, only a date and decimal hours.
+fn parse_csv(
+    content: &str,
+    tz: Tz,
+    slash_format: Option<&'static str>,
+) -> Result<(Vec<ImportedEntry>, Vec<ImportIssue>), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+
+    let column = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|h| names.iter().any(|name| h.trim().eq_ignore_ascii_case(name)))
+    };
+    let client_col = column(&["Client"]);
+    let project_col = column(&["Project"]);
+    let task_col = column(&["Task"]);
+    let notes_col = column(&["Description", "Notes"]);
+    let start_date_col = column(&["Start date", "Date"]);
+    let start_time_col = column(&["Start time"]);
+    let end_date_col = column(&["End date"]);
+    let end_time_col = column(&["End time"]);
+    let duration_col = column(&["Duration", "Duration (h)", "Duration (decimal)", "Hours"]);
+    let billable_col = column(&["Billable", "Billable?"]);
+
+    let Some(start_date_col) = start_date_col else {
+        return Err("The file has no start date column; is it a time entry export?".to_string());
+    };
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut day_cursor: HashMap<NaiveDate, NaiveDateTime> = HashMap::new();
+
+    let mut records = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let line = index + 2; // header is line 1
+        match record {
+            Ok(record) => records.push((line, record)),
+            Err(e) => errors.push(ImportIssue { line, message: e.to_string() }),
+        }
+    }
+
+    let date_cols = [Some(start_date_col), end_date_col];
+    let slash_format = match slash_format {
+        Some(format) => Some(format),
+        None => detect_slash_format(records.iter().flat_map(|(_, record)| {
+            date_cols.iter().flatten().filter_map(|&c| record.get(c)).map(str::trim)
+        }))?,
+    };
+
+    for (line, record) in records {
+        let cell = |col: Option<usize>| {
+            col.and_then(|c| record.get(c))
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+
+        let result = (|| -> Result<ImportedEntry, String> {
+            let date = parse_date(cell(Some(start_date_col)).ok_or("Missing date")?, slash_format)?;
+            let duration = cell(duration_col).map(parse_duration).transpose()?;
+
+            let (start, end) = match cell(start_time_col) {
+                Some(time) => {
+                    let start = date.and_time(parse_time(time)?);
+                    let end = match (cell(end_date_col), cell(end_time_col)) {
+                        (Some(end_date), Some(end_time)) => {
+                            parse_date(end_date, slash_format)?.and_time(parse_time(end_time)?)
+                        }
+                        _ => start + duration.ok_or("Missing duration or end time")?,
+                    };
+                    (start, end)
+                }
+                None => {
+                    let duration = duration.ok_or("Missing duration")?;
+                    let cursor = day_cursor.entry(date).or_insert_with(|| {
+                        date.and_time(NaiveTime::from_hms_opt(DAY_START_HOUR, 0, 0).unwrap())
+                    });
+                    let start = *cursor;
+                    *cursor = start + duration;
+                    (start, start + duration)
+                }
+            };
+
+            let notes = match (cell(notes_col), cell(task_col)) {
+                (Some(notes), _) => Some(notes.to_string()),
+                (None, Some(task)) => Some(task.to_string()),
+                (None, None) => None,
+            };
+
+            build_entry(
+                line,
+                cell(client_col),
+                cell(project_col),
+                local_to_utc(start, tz)?,
+                local_to_utc(end, tz)?,
+                notes,
+                cell(billable_col).map_or(true, parse_bool),
+            )
+        })();
+
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(message) => errors.push(ImportIssue { line, message }),
+        }
+    }
+
+    Ok((entries, errors))
+}
+
+fn parse_json(content: &str, source: &str, tz: Tz) -> Result<(Vec<ImportedEntry>, Vec<ImportIssue>), String> {
+    let root: Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    // Exports are either a bare array or wrapped by the report/API envelope
+    let items = match &root {
+        Value::Array(items) => items.clone(),
+        Value::Object(_) => ["data", "time_entries", "timeentries", "timeEntries"]
+            .iter()
+            .find_map(|key| root.get(key).and_then(Value::as_array).cloned())
+            .ok_or("No time entries found in the JSON file")?,
+        _ => return Err("No time entries found in the JSON file".to_string()),
+    };
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut day_cursor: HashMap<NaiveDate, NaiveDateTime> = HashMap::new();
+
+    for (index, item) in items.iter().enumerate() {
+        let line = index + 1;
+        let result = match source {
+            "toggl" => toggl_json_entry(line, item),
+            "clockify" => clockify_json_entry(line, item),
+            _ => harvest_json_entry(line, item, tz, &mut day_cursor),
+        };
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(message) => errors.push(ImportIssue { line, message }),
+        }
+    }
+
+    Ok((entries, errors))
+}
+
+/// Toggl API v9 time entries and detailed report rows
+fn toggl_json_entry(line: usize, item: &Value) -> Result<ImportedEntry, String> {
+    let start = parse_timestamp(json_str(item, &["start"]).ok_or("Missing start time")?)?;
+    let end = match json_str(item, &["stop", "end"]) {
+        Some(end) => parse_timestamp(end)?,
+        None => {
+            // Running entries have a negative duration
+            let seconds = item
+                .get("duration")
+                .and_then(Value::as_i64)
+                .or_else(|| item.get("dur").and_then(Value::as_i64).map(|ms| ms / 1000))
+                .filter(|s| *s > 0)
+                .ok_or("Entry is still running or has no duration")?;
+            start + Duration::seconds(seconds)
+        }
+    };
+
+    build_entry(
+        line,
+        json_str(item, &["client_name", "client"]),
+        json_str(item, &["project_name", "project"]),
+        start,
+        end,
+        json_str(item, &["description"]).map(str::to_string),
+        json_bool(item, &["billable", "is_billable"]),
+    )
+}
+
+/// Clockify detailed report rows and API time entries (with hydrated project)
+fn clockify_json_entry(line: usize, item: &Value) -> Result<ImportedEntry, String> {
+    let interval = item.get("timeInterval").ok_or("Missing timeInterval")?;
+    let start = parse_timestamp(json_str(interval, &["start"]).ok_or("Missing start time")?)?;
+    let end = parse_timestamp(json_str(interval, &["end"]).ok_or("Entry is still running")?)?;
+
+    let project = item.get("project").unwrap_or(&Value::Null);
+    build_entry(
+        line,
+        json_str(item, &["clientName"]).or_else(|| json_str(project, &["clientName"])),
+        json_str(item, &["projectName"]).or_else(|| json_str(project, &["name"])),
+        start,
+        end,
+        json_str(item, &["description"]).map(str::to_string),
+        json_bool(item, &["billable"]),
+    )
+}
+
+/// Harvest API v2 time entries; start/end times are only present when the
+/// account tracks timestamps, otherwise entries are laid out per day.
+fn harvest_json_entry(
+    line: usize,
+    item: &Value,
+    tz: Tz,
+    day_cursor: &mut HashMap<NaiveDate, NaiveDateTime>,
+) -> Result<ImportedEntry, String> {
+    // The API writes ISO dates, which need no day/month guess
+    let date = parse_date(json_str(item, &["spent_date"]).ok_or("Missing spent_date")?, None)?;
+    let hours = item.get("hours").and_then(Value::as_f64).ok_or("Missing hours")?;
+    let duration = Duration::seconds((hours * 3600.0).round() as i64);
+
+    let (start, end) = match (json_str(item, &["started_time"]), json_str(item, &["ended_time"])) {
+        (Some(started), Some(ended)) => (date.and_time(parse_time(started)?), date.and_time(parse_time(ended)?)),
+        (Some(started), None) => {
+            let start = date.and_time(parse_time(started)?);
+            (start, start + duration)
+        }
+        _ => {
+            let cursor = day_cursor
+                .entry(date)
+                .or_insert_with(|| date.and_time(NaiveTime::from_hms_opt(DAY_START_HOUR, 0, 0).unwrap()));
+            let start = *cursor;
+            *cursor = start + duration;
+            (start, start + duration)
+        }
+    };
+
+    let client = item.get("client").unwrap_or(&Value::Null);
+    let project = item.get("project").unwrap_or(&Value::Null);
+    build_entry(
+        line,
+        json_str(client, &["name"]),
+        json_str(project, &["name"]),
+        local_to_utc(start, tz)?,
+        local_to_utc(end, tz)?,
+        json_str(item, &["notes"]).map(str::to_string),
+        json_bool(item, &["billable"]),
+    )
+}
+
+fn build_entry(
+    line: usize,
+    client: Option<&str>,
+    project: Option<&str>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    notes: Option<String>,
+    billable: bool,
+) -> Result<ImportedEntry, String> {
+    if end <= start {
+        return Err("End time is not after start time".to_string());
+    }
+
+    Ok(ImportedEntry {
+        line,
+        client: client.map(str::trim).filter(|c| !c.is_empty()).map(str::to_string),
+        project: project
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .unwrap_or(FALLBACK_PROJECT)
+            .to_string(),
+        start,
+        end,
+        notes: notes.filter(|n| !n.trim().is_empty()),
+        billable,
+    })
+}
+
+fn json_str<'a>(item: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|key| item.get(key).and_then(Value::as_str))
+        .filter(|v| !v.trim().is_empty())
+}
+
+fn json_bool(item: &Value, keys: &[&str]) -> bool {
+    keys.iter()
+        .find_map(|key| item.get(key).and_then(Value::as_bool))
+        .unwrap_or(true)
+}
+
+fn parse_bool(value: &str) -> bool {
+    !matches!(value.to_lowercase().as_str(), "no" | "false" | "0" | "n")
+}
+
+/// A date in one of `DATE_FORMATS`, or a slashed one read with `slash_format`
+fn parse_date(value: &str, slash_format: Option<&str>) -> Result<NaiveDate, String> {
+    DATE_FORMATS
+        .iter()
+        .chain(slash_format.as_ref())
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .ok_or_else(|| format!("Unrecognized date: {}", value))
+}
+
+/// The reading of slashed dates that fits the most dates of a file, `None` when
+/// there are no slashed dates. Fails when both fit equally well, which happens
+/// when no day in the file is past the 12th.
+fn detect_slash_format<'a>(dates: impl Iterator<Item = &'a str>) -> Result<Option<&'static str>, String> {
+    let slashed: Vec<&str> = dates.filter(|date| date.contains('/')).collect();
+    let fits = |format: &str| {
+        slashed
+            .iter()
+            .filter(|date| NaiveDate::parse_from_str(date, format).is_ok())
+            .count()
+    };
+
+    match (fits(MONTH_FIRST), fits(DAY_FIRST)) {
+        (0, 0) => Ok(None),
+        (month_first, day_first) if month_first > day_first => Ok(Some(MONTH_FIRST)),
+        (month_first, day_first) if day_first > month_first => Ok(Some(DAY_FIRST)),
+        _ => Err(format!(
+            "Dates such as {} can be read as month/day or day/month; choose the date format of the export",
+            slashed[0]
+        )),
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| format!("Unrecognized time: {}", value))
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| format!("Unrecognized date and time: {}", value))
+}
+
+/// "HH:MM:SS" (Toggl, Clockify) or decimal hours (Harvest, Clockify)
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let parts: Vec<&str> = value.split(':').collect();
+    let seconds = if parts.len() > 1 {
+        parts
+            .iter()
+            .try_fold(0i64, |total, part| part.parse::<i64>().map(|n| total * 60 + n))
+            .map(|total| if parts.len() == 2 { total * 60 } else { total })
+            .ok()
+    } else {
+        value
+            .replace(',', ".")
+            .parse::<f64>()
+            .ok()
+            .map(|hours| (hours * 3600.0).round() as i64)
+    };
+
+    seconds
+        .map(Duration::seconds)
+        .ok_or_else(|| format!("Unrecognized duration: {}", value))
+}
+
+/// Exports without an offset are read in the user's time zone
+fn local_to_utc(naive: NaiveDateTime, tz: Tz) -> Result<DateTime<Utc>, String> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok_or_else(|| format!("Local time does not exist: {}", naive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parses_clock_and_decimal_durations() {
+        assert_eq!(parse_duration("01:30:15").unwrap(), Duration::seconds(5415));
+        assert_eq!(parse_duration("2:05").unwrap(), Duration::seconds(7500));
+        assert_eq!(parse_duration("1.5").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("0,25").unwrap(), Duration::minutes(15));
+        assert!(parse_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn slashed_dates_follow_the_file() {
+        let us = ["03/04/2024", "03/25/2024"];
+        assert_eq!(detect_slash_format(us.into_iter()).unwrap(), Some(MONTH_FIRST));
+
+        let european = ["03/04/2024", "25/03/2024"];
+        assert_eq!(detect_slash_format(european.into_iter()).unwrap(), Some(DAY_FIRST));
+
+        let iso = ["2024-03-04"];
+        assert_eq!(detect_slash_format(iso.into_iter()).unwrap(), None);
+
+        let ambiguous = ["03/04/2024", "05/06/2024"];
+        assert!(detect_slash_format(ambiguous.into_iter()).is_err());
+    }
+
+    #[test]
+    fn parse_date_needs_a_reading_for_slashed_dates() {
+        assert_eq!(parse_date("2024-03-04", None).unwrap(), date(2024, 3, 4));
+        assert_eq!(parse_date("04.03.2024", None).unwrap(), date(2024, 3, 4));
+        assert_eq!(parse_date("03/04/2024", Some(MONTH_FIRST)).unwrap(), date(2024, 3, 4));
+        assert_eq!(parse_date("03/04/2024", Some(DAY_FIRST)).unwrap(), date(2024, 4, 3));
+        assert!(parse_date("03/04/2024", None).is_err());
+    }
+
+    #[test]
+    fn european_csv_reads_day_first() {
+        let csv = "Project,Client,Description,Start date,Start time,End date,End time\n\
+                   Web,Acme,Review,03/04/2024,09:00,03/04/2024,10:30\n\
+                   Web,Acme,Fixes,25/04/2024,14:00,25/04/2024,15:00\n";
+
+        let (entries, errors) = parse_csv(csv, chrono_tz::UTC, None).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(entries[0].start.to_rfc3339(), "2024-04-03T09:00:00+00:00");
+        assert_eq!(entries[0].end - entries[0].start, Duration::minutes(90));
+        assert_eq!(entries[1].client.as_deref(), Some("Acme"));
+    }
+
+    #[test]
+    fn ambiguous_csv_is_rejected_unless_told_the_format() {
+        let csv = "Project,Date,Hours\nWeb,03/04/2024,2\nWeb,05/06/2024,1.5\n";
+
+        assert!(parse_csv(csv, chrono_tz::UTC, None).is_err());
+
+        let (entries, errors) = parse_csv(csv, chrono_tz::UTC, Some(DAY_FIRST)).unwrap();
+        assert!(errors.is_empty());
+        // Harvest-style rows without times are laid out from the start of the day
+        assert_eq!(entries[0].start.to_rfc3339(), "2024-04-03T09:00:00+00:00");
+        assert_eq!(entries[1].start.to_rfc3339(), "2024-06-05T09:00:00+00:00");
+        assert_eq!(entries[1].project, "Web");
+    }
+
+    #[test]
+    fn names_created_in_one_spelling_are_not_also_matched() {
+        let conn = crate::database::test_connection();
+        let csv = "Project,Client,Description,Start date,Start time,End date,End time\n\
+                   Web,Acme,Review,2024-04-03,09:00,2024-04-03,10:30\n\
+                   web,ACME,Fixes,2024-04-04,14:00,2024-04-04,15:00\n";
+        let (entries, _) = parse_csv(csv, chrono_tz::UTC, None).unwrap();
+        let mut report = ImportReport {
+            source: "toggl".to_string(),
+            dry_run: true,
+            entries_read: entries.len(),
+            clients_created: Vec::new(),
+            clients_matched: Vec::new(),
+            projects_created: Vec::new(),
+            projects_matched: Vec::new(),
+            sessions_imported: 0,
+            imported_seconds: 0,
+            duplicates_skipped: 0,
+            conflicts: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        apply_import(&conn, &entries, chrono_tz::UTC, &mut report).unwrap();
+
+        assert_eq!(report.projects_created, vec!["Web".to_string()]);
+        assert!(report.projects_matched.is_empty());
+        assert_eq!(report.clients_created, vec!["Acme".to_string()]);
+        assert!(report.clients_matched.is_empty());
+        assert_eq!(report.sessions_imported, 2);
+    }
+}
@@ -0,0 +1,561 @@
+use crate::commands::settings;
+use crate::models::{Invoice, InvoiceDetail, InvoiceItem};
+use chrono::{Datelike, NaiveDate, Utc};
+use rusqlite::Connection;
+use std::sync::Mutex;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::pdf_layout::{FontStyle, PdfLayout, MARGIN_LEFT, MARGIN_RIGHT};
+use printpdf::{Color, Rgb};
+
+const INVOICE_COLUMNS: &str = "id, number, client_id, period_start, period_end, issue_date, currency,
+     subtotal, tax_rate, tax_amount, total, notes, created_at";
+
+fn invoice_from_row(row: &rusqlite::Row) -> rusqlite::Result<Invoice> {
+    Ok(Invoice {
+        id: row.get(0)?,
+        number: row.get(1)?,
+        client_id: row.get(2)?,
+        period_start: row.get(3)?,
+        period_end: row.get(4)?,
+        issue_date: row.get(5)?,
+        currency: row.get(6)?,
+        subtotal: row.get(7)?,
+        tax_rate: row.get(8)?,
+        tax_amount: row.get(9)?,
+        total: row.get(10)?,
+        notes: row.get(11)?,
+        created_at: row.get::<_, String>(12)?.parse().unwrap(),
+    })
+}
+
+/// Sessions of one project gathered into a single invoice line
+struct ProjectLine {
+    project_id: String,
+    project_name: String,
+    session_ids: Vec<String>,
+    billable_seconds: i64,
+    rate: Option<f64>,
+    currency: Option<String>,
+}
+
+fn round_money(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Invoice numbers are `<prefix><year>-<sequence>`, e.g. "INV-2026-0007"
+fn next_invoice_number(conn: &Connection, year: i32) -> Result<String, String> {
+    let prefix = settings::read_setting(conn, settings::INVOICE_NUMBER_PREFIX)?
+        .unwrap_or_else(|| "INV-".to_string());
+    let series = format!("{}{}-", prefix, year);
+
+    // substr() counts characters, not bytes, so a prefix like "FAC-Nº" matches too
+    let mut stmt = conn
+        .prepare("SELECT number FROM invoices WHERE substr(number, 1, ?2) = ?1")
+        .map_err(|e| e.to_string())?;
+
+    let last_sequence = stmt
+        .query_map(rusqlite::params![&series, series.chars().count() as i64], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|number| number.ok()?.strip_prefix(&series)?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+
+    Ok(format!("{}{:04}", series, last_sequence + 1))
+}
+
+fn fetch_invoice_detail(conn: &Connection, invoice_id: &str) -> Result<InvoiceDetail, String> {
+    let invoice = conn
+        .query_row(
+            &format!("SELECT {} FROM invoices WHERE id = ?1", INVOICE_COLUMNS),
+            [invoice_id],
+            invoice_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, invoice_id, project_id, description, hours, rate, amount
+             FROM invoice_items WHERE invoice_id = ?1 ORDER BY description ASC"
+        )
+        .map_err(|e| e.to_string())?;
+
+    let items = stmt
+        .query_map([invoice_id], |row| {
+            Ok(InvoiceItem {
+                id: row.get(0)?,
+                invoice_id: row.get(1)?,
+                project_id: row.get(2)?,
+                description: row.get(3)?,
+                hours: row.get(4)?,
+                rate: row.get(5)?,
+                amount: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<InvoiceItem>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let session_count = conn
+        .query_row(
+            "SELECT COUNT(*) FROM time_sessions WHERE invoice_id = ?1",
+            [invoice_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(InvoiceDetail {
+        invoice,
+        items,
+        session_count,
+    })
+}
+
+/// Invoice every billable, not yet invoiced session of a client in the date range,
+/// one line item per project. The invoiced sessions are locked from edits.
+#[tauri::command]
+pub fn create_invoice(
+    db: State<Mutex<Connection>>,
+    client_id: String,
+    start_date: String,
+    end_date: String,
+    tax_rate: Option<f64>,
+    notes: Option<String>,
+    file_path: Option<String>,
+) -> Result<InvoiceDetail, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let tax_rate = tax_rate.unwrap_or(0.0);
+
+    let mut groups: Vec<ProjectLine> = Vec::new();
+    {
+        let mut stmt = tx
+            .prepare(
+                "SELECT ts.id, ts.project_id, p.name, COALESCE(ts.duration_seconds, 0),
+                        COALESCE(p.hourly_rate, c.hourly_rate), COALESCE(p.currency, c.currency)
+                 FROM time_sessions ts
+                 JOIN projects p ON ts.project_id = p.id
+                 JOIN clients c ON p.client_id = c.id
+                 WHERE c.id = ?1
+                 AND ts.is_running = 0
+                 AND ts.billable = 1
+                 AND ts.invoice_id IS NULL
+                 AND local_date(ts.start_time, ts.timezone) >= ?2
+                 AND local_date(ts.start_time, ts.timezone) <= ?3
+                 ORDER BY p.name ASC, ts.start_time ASC"
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([&client_id, &start_date, &end_date], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<f64>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        for row in rows {
+            let (session_id, project_id, project_name, seconds, rate, currency) =
+                row.map_err(|e| e.to_string())?;
+
+            match groups.iter_mut().find(|g| g.project_id == project_id) {
+                Some(group) => {
+                    group.session_ids.push(session_id);
+                    group.billable_seconds += seconds;
+                }
+                None => groups.push(ProjectLine {
+                    project_id,
+                    project_name,
+                    session_ids: vec![session_id],
+                    billable_seconds: seconds,
+                    rate,
+                    currency,
+                }),
+            }
+        }
+    }
+
+    if groups.is_empty() {
+        return Err("No billable, uninvoiced sessions for this client in the selected period".to_string());
+    }
+
+    let currency = groups[0].currency.clone();
+    if groups.iter().any(|g| g.currency != currency) {
+        return Err("Projects in this period are billed in different currencies".to_string());
+    }
+
+    let invoice_id = Uuid::new_v4().to_string();
+    let issue_date = now.with_timezone(&crate::timezone::user_timezone(&tx)).date_naive();
+    let number = next_invoice_number(&tx, issue_date.year())?;
+
+    let mut items = Vec::new();
+    for group in &groups {
+        let rate = group
+            .rate
+            .ok_or_else(|| format!("Project \"{}\" has no hourly rate", group.project_name))?;
+        let hours = round_money(group.billable_seconds as f64 / 3600.0);
+        items.push(InvoiceItem {
+            id: Uuid::new_v4().to_string(),
+            invoice_id: invoice_id.clone(),
+            project_id: Some(group.project_id.clone()),
+            description: group.project_name.clone(),
+            hours,
+            rate,
+            amount: round_money(hours * rate),
+        });
+    }
+
+    let subtotal = round_money(items.iter().map(|i| i.amount).sum());
+    let tax_amount = round_money(subtotal * tax_rate / 100.0);
+
+    tx.execute(
+        "INSERT INTO invoices (id, number, client_id, period_start, period_end, issue_date, currency,
+         subtotal, tax_rate, tax_amount, total, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        rusqlite::params![
+            &invoice_id,
+            &number,
+            &client_id,
+            &start_date,
+            &end_date,
+            &issue_date.format("%Y-%m-%d").to_string(),
+            &currency,
+            &subtotal,
+            &tax_rate,
+            &tax_amount,
+            &round_money(subtotal + tax_amount),
+            &notes,
+            &now.to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for item in &items {
+        tx.execute(
+            "INSERT INTO invoice_items (id, invoice_id, project_id, description, hours, rate, amount)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                &item.id,
+                &item.invoice_id,
+                &item.project_id,
+                &item.description,
+                &item.hours,
+                &item.rate,
+                &item.amount
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for session_id in groups.iter().flat_map(|g| g.session_ids.iter()) {
+        tx.execute(
+            "UPDATE time_sessions SET invoice_id = ?1, updated_at = ?2 WHERE id = ?3",
+            rusqlite::params![&invoice_id, &now.to_rfc3339(), session_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let detail = fetch_invoice_detail(&tx, &invoice_id)?;
+
+    // Render before committing: if the document cannot be written, the number is
+    // not used up and the sessions stay unlocked
+    if let Some(file_path) = &file_path {
+        render_invoice_pdf(&tx, &detail, file_path)?;
+    }
+    if let Err(e) = tx.commit() {
+        if let Some(file_path) = &file_path {
+            let _ = std::fs::remove_file(file_path);
+        }
+        return Err(e.to_string());
+    }
+
+    Ok(detail)
+}
+
+pub(crate) fn query_all_invoices(conn: &Connection) -> Result<(Vec<Invoice>, Vec<InvoiceItem>), String> {
+    let invoices = conn
+        .prepare(&format!("SELECT {} FROM invoices ORDER BY number ASC", INVOICE_COLUMNS))
+        .map_err(|e| e.to_string())?
+        .query_map([], invoice_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<Invoice>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let items = invoices
+        .iter()
+        .map(|invoice| fetch_invoice_detail(conn, &invoice.id).map(|detail| detail.items))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    Ok((invoices, items))
+}
+
+#[tauri::command]
+pub fn get_invoices(
+    db: State<Mutex<Connection>>,
+    client_id: Option<String>,
+) -> Result<Vec<Invoice>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM invoices WHERE ?1 IS NULL OR client_id = ?1 ORDER BY number DESC",
+            INVOICE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let invoices = stmt
+        .query_map([&client_id], invoice_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<Invoice>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(invoices)
+}
+
+#[tauri::command]
+pub fn get_invoice(db: State<Mutex<Connection>>, id: String) -> Result<InvoiceDetail, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    fetch_invoice_detail(&conn, &id)
+}
+
+#[tauri::command]
+pub fn export_invoice_pdf(
+    db: State<Mutex<Connection>>,
+    id: String,
+    file_path: String,
+) -> Result<(), String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let detail = fetch_invoice_detail(&conn, &id)?;
+    render_invoice_pdf(&conn, &detail, &file_path)
+}
+
+/// Void an invoice: its sessions are unlocked and can be invoiced again
+#[tauri::command]
+pub fn void_invoice(db: State<Mutex<Connection>>, id: String) -> Result<(), String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE time_sessions SET invoice_id = NULL, updated_at = ?1 WHERE invoice_id = ?2",
+        rusqlite::params![&Utc::now().to_rfc3339(), &id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM invoice_items WHERE invoice_id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM invoices WHERE id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn format_money(amount: f64, currency: &Option<String>) -> String {
+    match currency {
+        Some(currency) => format!("{:.2} {}", amount, currency),
+        None => format!("{:.2}", amount),
+    }
+}
+
+fn format_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.format("%d/%m/%Y").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+fn render_invoice_pdf(conn: &Connection, detail: &InvoiceDetail, file_path: &str) -> Result<(), String> {
+    let invoice = &detail.invoice;
+
+    let (client_name, client_address, client_tax_id): (String, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT name, billing_address, tax_id FROM clients WHERE id = ?1",
+            [&invoice.client_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let issuer_name = settings::read_setting(conn, settings::INVOICE_ISSUER_NAME)?.unwrap_or_default();
+    let issuer_address = settings::read_setting(conn, settings::INVOICE_ISSUER_ADDRESS)?;
+    let issuer_tax_id = settings::read_setting(conn, settings::INVOICE_ISSUER_TAX_ID)?;
+
+    let mut layout = PdfLayout::new(
+        &format!("Factura {}", invoice.number),
+        &format!("Factura {} · {}", invoice.number, client_name),
+    )?;
+
+    let blue = Color::Rgb(Rgb::new(41.0/255.0, 128.0/255.0, 185.0/255.0, None));
+    let dark_gray = Color::Rgb(Rgb::new(52.0/255.0, 73.0/255.0, 94.0/255.0, None));
+    let light_gray = Color::Rgb(Rgb::new(0.85, 0.85, 0.85, None));
+
+    // Title and invoice metadata
+    layout.text("FACTURA", 26.0, MARGIN_LEFT, 0.0, FontStyle::Bold, &blue);
+    layout.text(&format!("Nº {}", invoice.number), 12.0, 140.0, 0.0, FontStyle::Bold, &dark_gray);
+    layout.text(
+        &format!("Fecha: {}", format_date(&invoice.issue_date)),
+        10.0,
+        140.0,
+        -6.0,
+        FontStyle::Regular,
+        &dark_gray,
+    );
+    layout.text(
+        &format!("Período: {} - {}", format_date(&invoice.period_start), format_date(&invoice.period_end)),
+        10.0,
+        140.0,
+        -11.0,
+        FontStyle::Regular,
+        &dark_gray,
+    );
+    layout.advance(25.0);
+
+    // Issuer (left) and recipient (right) blocks
+    let blocks = [
+        (MARGIN_LEFT, "EMISOR", issuer_name, issuer_address, issuer_tax_id),
+        (115.0, "CLIENTE", client_name, client_address, client_tax_id),
+    ];
+    let block_top = layout.y();
+    let mut lowest_y = block_top;
+    for (x, title, name, address, tax_id) in blocks {
+        let mut lines = layout.wrap(&name, 11.0, FontStyle::Bold, 75.0);
+        lines.extend(address.iter().flat_map(|a| layout.wrap(a, 10.0, FontStyle::Regular, 75.0)));
+        if let Some(tax_id) = tax_id {
+            lines.push(format!("NIF/CIF: {}", tax_id));
+        }
+
+        layout.text(title, 10.0, x, 0.0, FontStyle::Bold, &blue);
+        let mut dy = -6.0;
+        for (index, line) in lines.iter().enumerate() {
+            let style = if index == 0 { FontStyle::Bold } else { FontStyle::Regular };
+            layout.text(line, 10.0, x, dy, style, &dark_gray);
+            dy -= 5.0;
+        }
+        lowest_y = lowest_y.min(block_top + dy);
+    }
+    layout.advance(block_top - lowest_y + 10.0);
+
+    // Line items table
+    let draw_table_header = |layout: &PdfLayout| {
+        layout.text("Concepto", 10.0, MARGIN_LEFT, 0.0, FontStyle::Bold, &blue);
+        layout.text_right("Horas", 10.0, 125.0, 0.0, FontStyle::Bold, &blue);
+        layout.text_right("Tarifa", 10.0, 157.0, 0.0, FontStyle::Bold, &blue);
+        layout.text_right("Importe", 10.0, MARGIN_RIGHT, 0.0, FontStyle::Bold, &blue);
+        layout.rule(-2.0, MARGIN_LEFT, MARGIN_RIGHT, &blue, 1.0);
+    };
+
+    draw_table_header(&layout);
+    layout.advance(8.0);
+
+    for item in &detail.items {
+        let description = layout.wrap(&item.description, 10.0, FontStyle::Regular, 80.0);
+        let row_height = description.len() as f32 * 5.0 + 2.0;
+
+        if layout.y() - row_height < 40.0 {
+            layout.new_page();
+            draw_table_header(&layout);
+            layout.advance(8.0);
+        }
+
+        layout.text_right(&format!("{:.2}", item.hours), 10.0, 125.0, 0.0, FontStyle::Regular, &dark_gray);
+        layout.text_right(&format_money(item.rate, &invoice.currency), 10.0, 157.0, 0.0, FontStyle::Regular, &dark_gray);
+        layout.text_right(&format_money(item.amount, &invoice.currency), 10.0, MARGIN_RIGHT, 0.0, FontStyle::Regular, &dark_gray);
+        for line in &description {
+            layout.text(line, 10.0, MARGIN_LEFT, 0.0, FontStyle::Regular, &dark_gray);
+            layout.advance(5.0);
+        }
+
+        layout.rule(2.5, MARGIN_LEFT, MARGIN_RIGHT, &light_gray, 0.2);
+        layout.advance(2.0);
+    }
+
+    // Totals
+    layout.ensure_space(30.0);
+    layout.advance(5.0);
+
+    let totals = [
+        ("Subtotal".to_string(), format_money(invoice.subtotal, &invoice.currency), FontStyle::Regular),
+        (
+            format!("Impuestos ({}%)", invoice.tax_rate),
+            format_money(invoice.tax_amount, &invoice.currency),
+            FontStyle::Regular,
+        ),
+        ("TOTAL".to_string(), format_money(invoice.total, &invoice.currency), FontStyle::Bold),
+    ];
+    for (label, amount, style) in totals {
+        layout.text(&label, 11.0, 120.0, 0.0, style, &dark_gray);
+        layout.text_right(&amount, 11.0, MARGIN_RIGHT, 0.0, style, &dark_gray);
+        layout.advance(7.0);
+    }
+
+    if let Some(notes) = invoice.notes.as_deref().filter(|n| !n.is_empty()) {
+        layout.advance(5.0);
+        layout.paragraph(notes, 9.0, MARGIN_LEFT, MARGIN_RIGHT - MARGIN_LEFT, 4.5, FontStyle::Regular, &dark_gray);
+    }
+
+    layout.save(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    fn insert_invoice(conn: &Connection, number: &str) -> rusqlite::Result<usize> {
+        conn.execute(
+            "INSERT INTO invoices (id, number, client_id, period_start, period_end, issue_date,
+             subtotal, tax_amount, total, created_at)
+             VALUES (?1, ?2, 'c1', '2026-01-01', '2026-01-31', '2026-02-01', 0, 0, 0, '2026-02-01T00:00:00Z')",
+            [&Uuid::new_v4().to_string(), number],
+        )
+    }
+
+    fn connection_with_client() -> Connection {
+        let conn = test_connection();
+        conn.execute(
+            "INSERT INTO clients (id, name, created_at, updated_at) VALUES ('c1', 'Client', '', '')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn numbers_follow_the_series_of_the_year() {
+        let conn = connection_with_client();
+        assert_eq!(next_invoice_number(&conn, 2026).unwrap(), "INV-2026-0001");
+
+        insert_invoice(&conn, "INV-2026-0001").unwrap();
+        insert_invoice(&conn, "INV-2026-0009").unwrap();
+        insert_invoice(&conn, "INV-2025-0042").unwrap();
+
+        assert_eq!(next_invoice_number(&conn, 2026).unwrap(), "INV-2026-0010");
+        assert_eq!(next_invoice_number(&conn, 2025).unwrap(), "INV-2025-0043");
+    }
+
+    #[test]
+    fn numbers_continue_with_non_ascii_prefix() {
+        let conn = connection_with_client();
+        settings::write_setting(&conn, settings::INVOICE_NUMBER_PREFIX, "FAC-Nº").unwrap();
+
+        insert_invoice(&conn, "FAC-Nº2026-0001").unwrap();
+
+        assert_eq!(next_invoice_number(&conn, 2026).unwrap(), "FAC-Nº2026-0002");
+    }
+
+    #[test]
+    fn numbers_are_unique() {
+        let conn = connection_with_client();
+        insert_invoice(&conn, "INV-2026-0001").unwrap();
+
+        assert!(insert_invoice(&conn, "INV-2026-0001").is_err());
+    }
+}
@@ -0,0 +1,574 @@
+use crate::commands::{clients, projects, sessions, settings};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::types::{ToSqlOutput, ValueRef};
+use rusqlite::{Connection, OptionalExtension, ToSql};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// Days deleted items stay in the trash before they are purged for good
+pub const TRASH_RETENTION_DAYS: &str = "trash_retention_days";
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+/// How many of the latest operations undo reaches back through
+const UNDO_DEPTH: i64 = 20;
+/// Seconds between background trash purges
+const PURGE_INTERVAL_SECS: u64 = 3600;
+
+/// A column value as SQLite stored it, so blobs and reals come back as they were
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StoredValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl From<ValueRef<'_>> for StoredValue {
+    fn from(value: ValueRef) -> Self {
+        match value {
+            ValueRef::Null => StoredValue::Null,
+            ValueRef::Integer(n) => StoredValue::Integer(n),
+            ValueRef::Real(x) => StoredValue::Real(x),
+            ValueRef::Text(bytes) => StoredValue::Text(String::from_utf8_lossy(bytes).into_owned()),
+            ValueRef::Blob(bytes) => StoredValue::Blob(bytes.to_vec()),
+        }
+    }
+}
+
+impl ToSql for StoredValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(match self {
+            StoredValue::Null => ValueRef::Null,
+            StoredValue::Integer(n) => ValueRef::Integer(*n),
+            StoredValue::Real(x) => ValueRef::Real(*x),
+            StoredValue::Text(s) => ValueRef::Text(s.as_bytes()),
+            StoredValue::Blob(bytes) => ValueRef::Blob(bytes),
+        }))
+    }
+}
+
+/// Rows of one table as they were before an operation, every column included
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct TableImage {
+    table: String,
+    rows: Vec<BTreeMap<String, StoredValue>>,
+}
+
+/// A journaled operation, as listed in the trash and the undo history
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub operation: String, // "delete_session", "delete_project" or "delete_client"
+    pub target_id: String,
+    pub label: String,
+    pub row_count: usize, // rows the operation removed or changed
+    pub undone: bool,
+    pub created_at: DateTime<Utc>,
+    pub purge_at: DateTime<Utc>,
+}
+
+/// Before-image of the rows of `table` matching `filter`, which binds the id as ?1
+pub(crate) fn capture(conn: &Connection, table: &str, filter: &str, id: &str) -> Result<TableImage, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} WHERE {}", table, filter))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+
+    let rows = stmt
+        .query_map([id], |row| {
+            let mut values = BTreeMap::new();
+            for (index, column) in columns.iter().enumerate() {
+                values.insert(column.clone(), StoredValue::from(row.get_ref(index)?));
+            }
+            Ok(values)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(TableImage {
+        table: table.to_string(),
+        rows,
+    })
+}
+
+/// Capture the rows of `table` matching `filter`, then delete them
+pub(crate) fn remove_rows(conn: &Connection, table: &str, filter: &str, id: &str) -> Result<TableImage, String> {
+    let image = capture(conn, table, filter, id)?;
+    conn.execute(&format!("DELETE FROM {} WHERE {}", table, filter), [id])
+        .map_err(|e| e.to_string())?;
+    Ok(image)
+}
+
+/// Put the captured rows back, overwriting whatever holds their keys now. An upsert
+/// rather than `INSERT OR REPLACE`, whose delete would cascade to the row's children.
+fn restore_image(conn: &Connection, image: &TableImage) -> Result<(), String> {
+    for row in &image.rows {
+        let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+        let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+        let assignments: Vec<String> = columns.iter().map(|c| format!("{} = excluded.{}", c, c)).collect();
+
+        conn.execute(
+            &format!(
+                "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT DO UPDATE SET {}",
+                image.table,
+                columns.join(", "),
+                placeholders.join(", "),
+                assignments.join(", ")
+            ),
+            rusqlite::params_from_iter(row.values()),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn text<'a>(row: &'a BTreeMap<String, StoredValue>, column: &str) -> Option<&'a str> {
+    match row.get(column) {
+        Some(StoredValue::Text(value)) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+/// Captured session rows that would now overlap a session recorded since they were
+/// removed; checked before the images go back, when only the newer sessions are there
+fn ensure_no_new_overlaps(conn: &Connection, images: &[TableImage]) -> Result<(), String> {
+    let now = Utc::now();
+    let restored_sessions = images.iter().filter(|image| image.table == "time_sessions");
+    for row in restored_sessions.flat_map(|image| &image.rows) {
+        let (Some(id), Some(project_id)) = (text(row, "id"), text(row, "project_id")) else {
+            continue;
+        };
+        let parse = |column| text(row, column).and_then(|value| value.parse::<DateTime<Utc>>().ok());
+        let Some(start) = parse("start_time") else {
+            continue;
+        };
+        let end = parse("end_time").unwrap_or(now);
+
+        if let Some(other) = sessions::find_overlapping_session(conn, project_id, start, end, Some(id))? {
+            return Err(format!(
+                "The session would overlap one recorded since (started {})",
+                other.start_time.to_rfc3339()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Restored sessions must still belong to a live project, one not archived since,
+/// and fall outside any invoice issued since `removed_at`
+fn ensure_sessions_restorable(conn: &Connection, images: &[TableImage], removed_at: &str) -> Result<(), String> {
+    let restored_projects: Vec<&str> = images
+        .iter()
+        .filter(|image| image.table == "projects")
+        .flat_map(|image| &image.rows)
+        .filter_map(|row| text(row, "id"))
+        .collect();
+
+    let restored_sessions = images.iter().filter(|image| image.table == "time_sessions");
+    for row in restored_sessions.flat_map(|image| &image.rows) {
+        let session_id = text(row, "id").unwrap_or("");
+        let project_id = text(row, "project_id").unwrap_or("");
+
+        // A session whose project was deleted afterwards would be left dangling
+        let status: Option<String> = conn
+            .query_row("SELECT status FROM projects WHERE id = ?1", [project_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        match status.as_deref() {
+            None => return Err("The session's project is deleted; restore the project first".to_string()),
+            Some("archived") if !restored_projects.contains(&project_id) => {
+                return Err("The session's project has been archived since".to_string())
+            }
+            _ => {}
+        }
+
+        let invoice_number: Option<String> = conn
+            .query_row(
+                "SELECT i.number FROM time_sessions ts
+                 JOIN projects p ON ts.project_id = p.id
+                 JOIN invoices i ON i.client_id = p.client_id
+                 WHERE ts.id = ?1 AND ts.is_running = 0 AND ts.billable = 1 AND i.created_at > ?2
+                 AND local_date(ts.start_time, ts.timezone) BETWEEN i.period_start AND i.period_end
+                 LIMIT 1",
+                [session_id, removed_at],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(number) = invoice_number {
+            return Err(format!("Invoice {} has been issued for the session's period since", number));
+        }
+    }
+    Ok(())
+}
+
+/// Run a journaled operation, returning a label for it and the before-images of
+/// every row it removed or changed
+fn apply(conn: &Connection, operation: &str, target_id: &str) -> Result<(String, Vec<TableImage>), String> {
+    match operation {
+        "delete_session" => sessions::remove_session(conn, target_id),
+        "delete_project" => projects::remove_project(conn, target_id),
+        "delete_client" => clients::remove_client(conn, target_id),
+        other => Err(format!("Unknown operation: {}", other)),
+    }
+}
+
+/// Run `operation` on `target_id` and journal it. Operations undone so far can no
+/// longer be redone afterwards.
+pub(crate) fn perform(conn: &Connection, operation: &str, target_id: &str) -> Result<(), String> {
+    let (label, images) = apply(conn, operation, target_id)?;
+    let images = serde_json::to_string(&images).map_err(|e| e.to_string())?;
+
+    conn.execute("DELETE FROM operation_journal WHERE undone_at IS NOT NULL", [])
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO operation_journal (operation, target_id, label, images, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![operation, target_id, &label, &images, &Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn retention_days(conn: &Connection) -> Result<i64, String> {
+    Ok(settings::read_setting(conn, TRASH_RETENTION_DAYS)?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+/// Entries matching `filter`, newest first
+fn query_entries(conn: &Connection, filter: &str, limit: i64) -> Result<Vec<JournalEntry>, String> {
+    let retention = Duration::days(retention_days(conn)?);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, operation, target_id, label, images, undone_at, created_at FROM operation_journal
+             WHERE {} ORDER BY created_at DESC, id DESC LIMIT ?1",
+            filter
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map([limit], |row| {
+            let images: Vec<TableImage> = serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default();
+            let created_at: DateTime<Utc> = row.get::<_, String>(6)?.parse().unwrap();
+            Ok(JournalEntry {
+                id: row.get(0)?,
+                operation: row.get(1)?,
+                target_id: row.get(2)?,
+                label: row.get(3)?,
+                row_count: images.iter().map(|image| image.rows.len()).sum(),
+                undone: row.get::<_, Option<String>>(5)?.is_some(),
+                created_at,
+                purge_at: created_at + retention,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<JournalEntry>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(entries)
+}
+
+fn fetch_entry(conn: &Connection, entry_id: i64) -> Result<JournalEntry, String> {
+    query_entries(conn, &format!("id = {}", entry_id), 1)?
+        .pop()
+        .ok_or_else(|| format!("Journal entry not found: {}", entry_id))
+}
+
+/// Restore the before-images of an entry and mark it undone. Sessions that no
+/// longer fit (overlapping newer ones, archived or invoiced since) fail the restore.
+fn revert(conn: &Connection, entry_id: i64) -> Result<JournalEntry, String> {
+    let (images, removed_at): (String, String) = conn
+        .query_row(
+            "SELECT images, created_at FROM operation_journal WHERE id = ?1",
+            [entry_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let images: Vec<TableImage> = serde_json::from_str(&images).map_err(|e| e.to_string())?;
+    ensure_no_new_overlaps(conn, &images)?;
+
+    // Children are captured (and so restored) before their parents; check the
+    // foreign keys once everything is back, when the transaction commits
+    conn.pragma_update(None, "defer_foreign_keys", true)
+        .map_err(|e| e.to_string())?;
+    for image in &images {
+        restore_image(conn, image)?;
+    }
+
+    ensure_sessions_restorable(conn, &images, &removed_at)?;
+
+    conn.execute(
+        "UPDATE operation_journal SET undone_at = ?1 WHERE id = ?2",
+        rusqlite::params![&Utc::now().to_rfc3339(), entry_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_entry(conn, entry_id)
+}
+
+/// Undo the latest operation not undone yet, among the last few. Returns it, or
+/// `None` when there is nothing to undo.
+#[tauri::command]
+pub fn undo_operation(db: State<Mutex<Connection>>) -> Result<Option<JournalEntry>, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let entry = query_entries(&tx, "1 = 1", UNDO_DEPTH)?
+        .into_iter()
+        .find(|entry| !entry.undone);
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+
+    let reverted = revert(&tx, entry.id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(Some(reverted))
+}
+
+/// Apply again the operation undone most recently. Returns it, or `None` when
+/// there is nothing to redo.
+#[tauri::command]
+pub fn redo_operation(db: State<Mutex<Connection>>) -> Result<Option<JournalEntry>, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let undone: Option<(i64, String, String)> = tx
+        .query_row(
+            "SELECT id, operation, target_id FROM operation_journal
+             WHERE undone_at IS NOT NULL ORDER BY undone_at DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some((entry_id, operation, target_id)) = undone else {
+        return Ok(None);
+    };
+
+    // Capture fresh images: the rows may have changed since they were restored
+    let (label, images) = apply(&tx, &operation, &target_id)?;
+    tx.execute(
+        "UPDATE operation_journal SET label = ?1, images = ?2, undone_at = NULL, created_at = ?3 WHERE id = ?4",
+        rusqlite::params![
+            &label,
+            &serde_json::to_string(&images).map_err(|e| e.to_string())?,
+            &Utc::now().to_rfc3339(),
+            entry_id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let entry = fetch_entry(&tx, entry_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(Some(entry))
+}
+
+/// The latest operations, newest first, for showing what undo and redo will do
+#[tauri::command]
+pub fn get_operation_history(db: State<Mutex<Connection>>) -> Result<Vec<JournalEntry>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    query_entries(&conn, "1 = 1", UNDO_DEPTH)
+}
+
+/// Deleted items that can still be restored, newest first
+#[tauri::command]
+pub fn get_trash(db: State<Mutex<Connection>>) -> Result<Vec<JournalEntry>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    query_entries(&conn, "undone_at IS NULL", i64::MAX)
+}
+
+/// Restore one item from the trash, whatever its place in the undo history
+#[tauri::command]
+pub fn restore_from_trash(db: State<Mutex<Connection>>, entry_id: i64) -> Result<JournalEntry, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    if fetch_entry(&tx, entry_id)?.undone {
+        return Err("The item is not in the trash".to_string());
+    }
+
+    let entry = revert(&tx, entry_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(entry)
+}
+
+/// Delete everything in the trash for good; returns how many items were purged
+#[tauri::command]
+pub fn empty_trash(db: State<Mutex<Connection>>) -> Result<usize, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM operation_journal WHERE undone_at IS NULL", [])
+        .map_err(|e| e.to_string())
+}
+
+/// Drop journal entries older than the retention period
+pub(crate) fn purge_expired<R: Runtime>(app: &AppHandle<R>) -> Result<usize, String> {
+    let db = app.state::<Mutex<Connection>>();
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let cutoff = Utc::now() - Duration::days(retention_days(&conn)?);
+    conn.execute(
+        "DELETE FROM operation_journal WHERE created_at < ?1",
+        [&cutoff.to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Purge expired trash at startup and then every hour
+pub fn setup_trash_purge<R: Runtime>(app: &tauri::App<R>) {
+    let app_handle = app.handle().clone();
+
+    std::thread::spawn(move || loop {
+        if let Err(e) = purge_expired(&app_handle) {
+            eprintln!("Trash purge failed: {}", e);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(PURGE_INTERVAL_SECS));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    /// Undo the latest entry the way the commands do, in a transaction so the
+    /// deferred foreign keys are checked on commit
+    fn revert_latest(conn: &Connection) {
+        let tx = conn.unchecked_transaction().unwrap();
+        let entry_id: i64 = tx
+            .query_row("SELECT MAX(id) FROM operation_journal", [], |row| row.get(0))
+            .unwrap();
+        revert(&tx, entry_id).unwrap();
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn restore_brings_back_every_column_with_its_type() {
+        let conn = test_connection();
+        // A column the journal knows nothing about, holding bytes that are not UTF-8
+        conn.execute_batch(
+            "ALTER TABLE clients ADD COLUMN logo BLOB;
+             INSERT INTO clients (id, name, hourly_rate, logo, created_at, updated_at)
+             VALUES ('c1', 'Client', 80.0, x'89ff00', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z');
+             INSERT INTO projects (id, name, client_id, created_at, updated_at) VALUES ('p1', 'Project', 'c1', '', '');",
+        )
+        .unwrap();
+        let snapshot = |conn: &Connection| -> (String, f64, Vec<u8>, String, Option<String>) {
+            conn.query_row(
+                "SELECT c.name, c.hourly_rate, c.logo, typeof(c.hourly_rate), p.client_id
+                 FROM clients c, projects p WHERE c.id = 'c1' AND p.id = 'p1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .unwrap()
+        };
+        let before = snapshot(&conn);
+
+        perform(&conn, "delete_client", "c1").unwrap();
+        let unlinked: Option<String> = conn
+            .query_row("SELECT client_id FROM projects WHERE id = 'p1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(unlinked, None);
+
+        revert_latest(&conn);
+        assert_eq!(snapshot(&conn), before);
+    }
+
+    #[test]
+    fn project_restore_keeps_sessions_pauses_and_tags() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, created_at, updated_at) VALUES ('p1', 'Project', '', '');
+             INSERT INTO tags (id, name, created_at, updated_at) VALUES ('t1', 'tag', '', '');
+             INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, created_at, updated_at)
+             VALUES ('s1', 'p1', '2024-01-01T09:00:00Z', '2024-01-01T10:00:00Z', 3000, '', '');
+             INSERT INTO session_tags (session_id, tag_id) VALUES ('s1', 't1');
+             INSERT INTO session_pauses (id, session_id, paused_at, resumed_at)
+             VALUES ('pa1', 's1', '2024-01-01T09:10:00Z', '2024-01-01T09:20:00Z');",
+        )
+        .unwrap();
+        let counts = |conn: &Connection| -> (i64, i64, i64) {
+            conn.query_row(
+                "SELECT (SELECT COUNT(*) FROM time_sessions), (SELECT COUNT(*) FROM session_tags),
+                 (SELECT COUNT(*) FROM session_pauses)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap()
+        };
+
+        perform(&conn, "delete_project", "p1").unwrap();
+        assert_eq!(counts(&conn), (0, 0, 0));
+
+        revert_latest(&conn);
+        assert_eq!(counts(&conn), (1, 1, 1));
+    }
+
+    #[test]
+    fn clients_with_invoices_cannot_be_deleted() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO clients (id, name, created_at, updated_at) VALUES ('c1', 'Client', '', '');
+             INSERT INTO invoices (id, number, client_id, period_start, period_end, issue_date,
+             subtotal, tax_amount, total, created_at)
+             VALUES ('i1', 'INV-2024-0001', 'c1', '2024-01-01', '2024-01-31', '2024-02-01', 0, 0, 0, '');",
+        )
+        .unwrap();
+
+        assert!(perform(&conn, "delete_client", "c1").is_err());
+        let clients: i64 = conn.query_row("SELECT COUNT(*) FROM clients", [], |row| row.get(0)).unwrap();
+        assert_eq!(clients, 1);
+    }
+
+    #[test]
+    fn session_restore_is_refused_when_things_changed_since() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO clients (id, name, created_at, updated_at) VALUES ('c1', 'Client', '', '');
+             INSERT INTO projects (id, name, client_id, created_at, updated_at) VALUES ('p1', 'Project', 'c1', '', '');
+             INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, created_at, updated_at)
+             VALUES ('s1', 'p1', '2024-01-15T09:00:00Z', '2024-01-15T10:00:00Z', 3600,
+             '2024-01-15T09:00:00Z', '2024-01-15T09:00:00Z');",
+        )
+        .unwrap();
+        perform(&conn, "delete_session", "s1").unwrap();
+        let try_revert = |conn: &Connection| {
+            let tx = conn.unchecked_transaction().unwrap();
+            let entry_id: i64 = tx
+                .query_row("SELECT MAX(id) FROM operation_journal", [], |row| row.get(0))
+                .unwrap();
+            revert(&tx, entry_id)
+        };
+
+        // A session recorded over the same hour in the meantime
+        conn.execute(
+            "INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, created_at, updated_at)
+             VALUES ('s2', 'p1', '2024-01-15T09:30:00Z', '2024-01-15T10:30:00Z', 3600,
+             '2024-01-15T09:30:00Z', '2024-01-15T09:30:00Z')",
+            [],
+        )
+        .unwrap();
+        assert!(try_revert(&conn).unwrap_err().contains("overlap"));
+        conn.execute("DELETE FROM time_sessions WHERE id = 's2'", []).unwrap();
+
+        conn.execute("UPDATE projects SET status = 'archived' WHERE id = 'p1'", []).unwrap();
+        assert!(try_revert(&conn).unwrap_err().contains("archived"));
+        conn.execute("UPDATE projects SET status = 'active' WHERE id = 'p1'", []).unwrap();
+
+        conn.execute(
+            "INSERT INTO invoices (id, number, client_id, period_start, period_end, issue_date,
+             subtotal, tax_amount, total, created_at)
+             VALUES ('i1', 'INV-2024-0001', 'c1', '2024-01-01', '2024-01-31', '2024-02-01', 0, 0, 0, ?1)",
+            [&Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+        assert!(try_revert(&conn).unwrap_err().contains("INV-2024-0001"));
+        let sessions: i64 = conn.query_row("SELECT COUNT(*) FROM time_sessions", [], |row| row.get(0)).unwrap();
+        assert_eq!(sessions, 0);
+    }
+}
@@ -1,7 +1,19 @@
 pub mod clients;
 pub mod projects;
 pub mod sessions;
+pub mod overlaps;
+pub mod tasks;
+pub mod tags;
 pub mod stats;
+pub mod budget;
+pub mod targets;
+pub mod pomodoro;
+pub mod reminders;
 pub mod tray;
 pub mod export;
+pub mod backup;
+pub mod journal;
+pub mod import;
+pub mod invoices;
 pub mod notion;
+pub mod settings;
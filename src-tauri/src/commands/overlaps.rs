@@ -0,0 +1,278 @@
+use crate::commands::sessions::{self, SESSION_COLUMNS};
+use crate::models::TimeSession;
+use crate::timezone;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::State;
+
+/// Two sessions sharing time; `first` starts no later than `second`
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionOverlap {
+    pub first: TimeSession,
+    pub second: TimeSession,
+    pub overlap_seconds: i64,
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", value))
+}
+
+/// End of a session for overlap purposes; running sessions last until now
+fn effective_end(session: &TimeSession, now: DateTime<Utc>) -> DateTime<Utc> {
+    if session.is_running {
+        now
+    } else {
+        session.end_time.unwrap_or(session.start_time)
+    }
+}
+
+/// Conflicting pairs among sessions that start within `start_date..=end_date` (local
+/// days). By default only sessions of the same project conflict; `scope = "all"`
+/// also reports overlaps across projects, for single-timer workflows.
+#[tauri::command]
+pub fn get_session_overlaps(
+    db: State<Mutex<Connection>>,
+    start_date: String,
+    end_date: String,
+    scope: Option<String>,
+) -> Result<Vec<SessionOverlap>, String> {
+    let across_projects = match scope.as_deref() {
+        None | Some("project") => false,
+        Some("all") => true,
+        Some(other) => return Err(format!("Invalid overlap scope: {}", other)),
+    };
+    let (start, end) = (parse_date(&start_date)?, parse_date(&end_date)?);
+
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let user_tz = timezone::user_timezone(&conn);
+    let now = Utc::now();
+
+    // Padded by two days so every zone offset is caught, plus the day before so
+    // partners starting just ahead of the range are seen
+    let window_start = (start - Duration::days(3)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let window_end = (end + Duration::days(2)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM time_sessions
+             WHERE start_time < ?1 AND (is_running = 1 OR end_time > ?2)
+             ORDER BY start_time ASC",
+            SESSION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let candidates = stmt
+        .query_map([&window_end.to_rfc3339(), &window_start.to_rfc3339()], sessions::session_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<TimeSession>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let in_range = |session: &TimeSession| {
+        let tz = timezone::session_timezone(session.timezone.as_deref(), user_tz);
+        let date = timezone::local_date(session.start_time, tz);
+        date >= start && date <= end
+    };
+
+    // Sorted by start, so each session only needs comparing with the ones that
+    // begin before it ends
+    let mut overlaps = Vec::new();
+    for (index, first) in candidates.iter().enumerate() {
+        let first_end = effective_end(first, now);
+        for second in candidates[index + 1..].iter().take_while(|s| s.start_time < first_end) {
+            if !across_projects && second.project_id != first.project_id {
+                continue;
+            }
+            if !in_range(first) && !in_range(second) {
+                continue;
+            }
+
+            let overlap_seconds = (first_end.min(effective_end(second, now)) - second.start_time).num_seconds();
+            if overlap_seconds > 0 {
+                overlaps.push(SessionOverlap {
+                    first: first.clone(),
+                    second: second.clone(),
+                    overlap_seconds,
+                });
+            }
+        }
+    }
+
+    Ok(overlaps)
+}
+
+fn set_span(
+    conn: &Connection,
+    session: &TimeSession,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    let duration_seconds = end
+        .map(|end| sessions::active_seconds(conn, &session.id, start, end))
+        .transpose()?;
+
+    conn.execute(
+        "UPDATE time_sessions SET start_time = ?1, end_time = ?2, duration_seconds = ?3, updated_at = ?4
+         WHERE id = ?5",
+        rusqlite::params![
+            &start.to_rfc3339(),
+            &end.map(|e| e.to_rfc3339()),
+            &duration_seconds,
+            &Utc::now().to_rfc3339(),
+            &session.id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Move a session's pauses along with it
+fn shift_pauses(conn: &Connection, session_id: &str, offset: Duration) -> Result<(), String> {
+    for pause in sessions::query_session_pauses(conn, Some(session_id))? {
+        conn.execute(
+            "UPDATE session_pauses SET paused_at = ?1, resumed_at = ?2 WHERE id = ?3",
+            rusqlite::params![
+                &(pause.paused_at + offset).to_rfc3339(),
+                &pause.resumed_at.map(|r| (r + offset).to_rfc3339()),
+                &pause.id
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Resolve an overlap between two sessions, in one transaction:
+/// - "trim" ends the earlier session where the later one starts; when the later one
+///   lies inside it, the rest of the earlier session continues after it
+/// - "shift" moves the later session to start where the earlier one ends, keeping its length
+/// - "merge" folds both into the earlier session (same project only)
+///
+/// Returns the resulting sessions.
+#[tauri::command]
+pub fn resolve_session_overlap(
+    db: State<Mutex<Connection>>,
+    first_session_id: String,
+    second_session_id: String,
+    action: String,
+) -> Result<Vec<TimeSession>, String> {
+    if first_session_id == second_session_id {
+        return Err("Choose two different sessions".to_string());
+    }
+
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let resolved = resolve_overlap(&tx, &first_session_id, &second_session_id, &action, Utc::now())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(resolved)
+}
+
+fn resolve_overlap(
+    conn: &Connection,
+    first_session_id: &str,
+    second_session_id: &str,
+    action: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<TimeSession>, String> {
+    for id in [first_session_id, second_session_id] {
+        sessions::ensure_not_invoiced(conn, id)?;
+    }
+    let mut pair = [
+        sessions::fetch_session(conn, first_session_id)?,
+        sessions::fetch_session(conn, second_session_id)?,
+    ];
+    pair.sort_by_key(|s| s.start_time);
+    let [earlier, later] = pair;
+
+    let earlier_end = effective_end(&earlier, now);
+    if later.start_time >= earlier_end {
+        return Err("The sessions do not overlap".to_string());
+    }
+
+    let ids = match action {
+        "trim" => {
+            if earlier.is_running {
+                return Err("Stop the running session before trimming it".to_string());
+            }
+            if later.start_time <= earlier.start_time {
+                return Err("Both sessions start at the same time; merge them instead".to_string());
+            }
+            // A session inside the earlier one splits it, so the time after it is kept
+            let later_end = effective_end(&later, now);
+            let mut ids = vec![earlier.id.clone(), later.id.clone()];
+            if later_end < earlier_end {
+                ids.push(sessions::split_session_at(conn, &earlier, later_end, &earlier.project_id, now)?);
+            }
+            sessions::end_session_at(conn, &earlier, later.start_time, now)?;
+            ids
+        }
+        "shift" => {
+            if earlier.is_running {
+                return Err("Cannot shift a session after a running one".to_string());
+            }
+            let new_end = (!later.is_running)
+                .then(|| earlier_end + (effective_end(&later, now) - later.start_time));
+            if new_end.is_some_and(|end| end > now) {
+                return Err("Shifting would move the session into the future".to_string());
+            }
+            let clash = sessions::find_overlapping_session(
+                conn,
+                &later.project_id,
+                earlier_end,
+                new_end.unwrap_or(now),
+                Some(&later.id),
+            )?;
+            if clash.is_some_and(|s| s.id != earlier.id) {
+                return Err("Shifting would overlap another session".to_string());
+            }
+            shift_pauses(conn, &later.id, earlier_end - later.start_time)?;
+            set_span(conn, &later, earlier_end, new_end)?;
+            vec![earlier.id, later.id]
+        }
+        "merge" => {
+            sessions::merge_session_into(conn, &earlier, &later)?;
+            vec![earlier.id]
+        }
+        other => return Err(format!("Invalid overlap resolution: {}", other)),
+    };
+
+    ids.iter().map(|id| sessions::fetch_session(conn, id)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    #[test]
+    fn trimming_around_a_contained_session_keeps_the_time_after_it() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, created_at, updated_at) VALUES ('p1', 'Project', '', '');
+             INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, notes,
+             created_at, updated_at)
+             VALUES ('outer', 'p1', '2024-01-15T09:00:00+00:00', '2024-01-15T13:00:00+00:00', 14400, 'Notes',
+             '2024-01-15T09:00:00+00:00', '2024-01-15T09:00:00+00:00'),
+             ('inner', 'p1', '2024-01-15T10:00:00+00:00', '2024-01-15T11:00:00+00:00', 3600, NULL,
+             '2024-01-15T10:00:00+00:00', '2024-01-15T10:00:00+00:00');",
+        )
+        .unwrap();
+
+        let now = "2024-01-15T18:00:00Z".parse().unwrap();
+        let resolved = resolve_overlap(&conn, "outer", "inner", "trim", now).unwrap();
+
+        let spans: Vec<_> = resolved
+            .iter()
+            .map(|s| (s.start_time.to_rfc3339(), s.duration_seconds))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("2024-01-15T09:00:00+00:00".to_string(), Some(3600)),
+                ("2024-01-15T10:00:00+00:00".to_string(), Some(3600)),
+                ("2024-01-15T11:00:00+00:00".to_string(), Some(7200)),
+            ]
+        );
+        assert_eq!(resolved[2].notes.as_deref(), Some("Notes"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::commands::{projects, sessions};
+use crate::models::PomodoroStatus;
+use crate::tray_manager;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+
+/// Interval lengths; anything left out takes the classic 25/5/15 values with a
+/// long break every 4 pomodoros
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PomodoroConfig {
+    pub focus_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    pub cycles_before_long_break: u32,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        PomodoroConfig {
+            focus_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycles_before_long_break: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Focus,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    fn as_str(self) -> &'static str {
+        match self {
+            Phase::Focus => "focus",
+            Phase::ShortBreak => "short_break",
+            Phase::LongBreak => "long_break",
+        }
+    }
+}
+
+struct PomodoroRun {
+    project_id: String,
+    task_id: Option<String>,
+    config: PomodoroConfig,
+    phase: Phase,
+    phase_ends_at: DateTime<Utc>,
+    session_id: Option<String>,
+    completed: u32,
+}
+
+impl PomodoroRun {
+    fn minutes(&self, phase: Phase) -> i64 {
+        let minutes = match phase {
+            Phase::Focus => self.config.focus_minutes,
+            Phase::ShortBreak => self.config.short_break_minutes,
+            Phase::LongBreak => self.config.long_break_minutes,
+        };
+        minutes.max(1) as i64
+    }
+
+    fn status(&self) -> PomodoroStatus {
+        PomodoroStatus {
+            project_id: self.project_id.clone(),
+            task_id: self.task_id.clone(),
+            phase: self.phase.as_str().to_string(),
+            phase_ends_at: self.phase_ends_at,
+            remaining_seconds: (self.phase_ends_at - Utc::now()).num_seconds().max(0),
+            completed_pomodoros: self.completed,
+            session_id: self.session_id.clone(),
+        }
+    }
+}
+
+/// The active pomodoro, if any. Lock it before the database when both are needed.
+#[derive(Default)]
+pub struct PomodoroState(Mutex<Option<PomodoroRun>>);
+
+fn set_tray_title<R: Runtime>(app: &AppHandle<R>, title: Option<String>) {
+    if let Some(tray) = app.tray_by_id("main") {
+        let _ = tray.set_title(title);
+    }
+}
+
+fn notify<R: Runtime>(app: &AppHandle<R>, title: &str, body: String) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("Failed to show pomodoro notification: {}", e);
+    }
+}
+
+/// Stop the focus session, keeping its notes
+fn stop_focus_session<R: Runtime>(app: &AppHandle<R>, session_id: &str) -> Result<(), String> {
+    let db = app.state::<Mutex<Connection>>();
+    let session = {
+        let conn = db.lock().map_err(|e| e.to_string())?;
+        sessions::fetch_session(&conn, session_id)?
+    };
+
+    if session.is_running {
+        sessions::stop_session(db, session.id, session.notes)?;
+    }
+    Ok(())
+}
+
+/// Move to the next phase. A focus interval that ran its full length counts as a
+/// completed pomodoro on its session; breaks start a fresh session for the project.
+fn advance<R: Runtime>(app: &AppHandle<R>, run: &mut PomodoroRun, completed_focus: bool) -> Result<(), String> {
+    let db = app.state::<Mutex<Connection>>();
+    let project_name = projects::get_all_projects(db.clone())?
+        .into_iter()
+        .find(|p| p.id == run.project_id)
+        .map(|p| p.name)
+        .unwrap_or_default();
+
+    if run.phase == Phase::Focus {
+        if let Some(session_id) = run.session_id.take() {
+            stop_focus_session(app, &session_id)?;
+            if completed_focus {
+                let conn = db.lock().map_err(|e| e.to_string())?;
+                conn.execute(
+                    "UPDATE time_sessions SET pomodoros = pomodoros + 1, updated_at = ?1 WHERE id = ?2",
+                    rusqlite::params![&Utc::now().to_rfc3339(), &session_id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        if completed_focus {
+            run.completed += 1;
+        }
+
+        let cycles = run.config.cycles_before_long_break.max(1);
+        run.phase = if completed_focus && run.completed % cycles == 0 {
+            Phase::LongBreak
+        } else {
+            Phase::ShortBreak
+        };
+        notify(
+            app,
+            "Pomodoro completado",
+            format!("{}: descanso de {} min", project_name, run.minutes(run.phase)),
+        );
+    } else {
+        let session = sessions::start_session(db, run.project_id.clone(), run.task_id.clone())?;
+        run.session_id = Some(session.id);
+        run.phase = Phase::Focus;
+        notify(
+            app,
+            "De vuelta al trabajo",
+            format!("{}: {} min de enfoque", project_name, run.minutes(Phase::Focus)),
+        );
+    }
+
+    run.phase_ends_at = Utc::now() + Duration::minutes(run.minutes(run.phase));
+    tray_manager::refresh_tray_menu(app);
+    let _ = app.emit("pomodoro-phase-changed", run.status());
+    Ok(())
+}
+
+/// Start pomodoro mode on a project: a focus interval that times a session,
+/// alternating with breaks until stopped. An already running session of the
+/// project is adopted as the first focus interval.
+#[tauri::command]
+pub fn start_pomodoro<R: Runtime>(
+    app: AppHandle<R>,
+    db: State<Mutex<Connection>>,
+    pomodoro: State<PomodoroState>,
+    project_id: String,
+    task_id: Option<String>,
+    config: Option<PomodoroConfig>,
+) -> Result<PomodoroStatus, String> {
+    let mut active = pomodoro.0.lock().map_err(|e| e.to_string())?;
+    if active.is_some() {
+        return Err("A pomodoro is already running".to_string());
+    }
+
+    let running = sessions::get_running_sessions(db.clone())?
+        .into_iter()
+        .find(|s| s.project_id == project_id);
+    let session = match running {
+        Some(session) => session,
+        None => sessions::start_session(db, project_id.clone(), task_id.clone())?,
+    };
+
+    let config = config.unwrap_or_default();
+    let run = PomodoroRun {
+        project_id,
+        task_id: session.task_id.clone().or(task_id),
+        phase_ends_at: Utc::now() + Duration::minutes(config.focus_minutes.max(1) as i64),
+        config,
+        phase: Phase::Focus,
+        session_id: Some(session.id),
+        completed: 0,
+    };
+
+    let status = run.status();
+    *active = Some(run);
+    tray_manager::refresh_tray_menu(&app);
+    let _ = app.emit("pomodoro-phase-changed", &status);
+    Ok(status)
+}
+
+/// Leave pomodoro mode, stopping the focus session if one is running
+#[tauri::command]
+pub fn stop_pomodoro<R: Runtime>(app: AppHandle<R>, pomodoro: State<PomodoroState>) -> Result<(), String> {
+    let mut active = pomodoro.0.lock().map_err(|e| e.to_string())?;
+    let Some(run) = active.take() else {
+        return Ok(());
+    };
+
+    if let Some(session_id) = &run.session_id {
+        stop_focus_session(&app, session_id)?;
+    }
+
+    set_tray_title(&app, None);
+    tray_manager::refresh_tray_menu(&app);
+    let _ = app.emit("pomodoro-stopped", run.status());
+    Ok(())
+}
+
+/// End the current phase early; a skipped focus interval is not counted
+#[tauri::command]
+pub fn skip_pomodoro_phase<R: Runtime>(
+    app: AppHandle<R>,
+    pomodoro: State<PomodoroState>,
+) -> Result<PomodoroStatus, String> {
+    let mut active = pomodoro.0.lock().map_err(|e| e.to_string())?;
+    let run = active.as_mut().ok_or("No pomodoro is running")?;
+
+    advance(&app, run, false)?;
+    Ok(run.status())
+}
+
+/// Whether pomodoro mode is on; breaks leave no session running on purpose
+pub(crate) fn pomodoro_active<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<PomodoroState>()
+        .and_then(|state| state.0.lock().ok().map(|active| active.is_some()))
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn get_pomodoro_status(pomodoro: State<PomodoroState>) -> Result<Option<PomodoroStatus>, String> {
+    let active = pomodoro.0.lock().map_err(|e| e.to_string())?;
+    Ok(active.as_ref().map(|run| run.status()))
+}
+
+/// One engine tick: end pomodoro mode if its session was stopped elsewhere, switch
+/// phases when due and show the time left in the tray title
+fn tick<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    let pomodoro = app.state::<PomodoroState>();
+    let mut active = pomodoro.0.lock().map_err(|e| e.to_string())?;
+    let Some(run) = active.as_mut() else {
+        return Ok(false);
+    };
+
+    if let Some(session_id) = &run.session_id {
+        let db = app.state::<Mutex<Connection>>();
+        let still_running = {
+            let conn = db.lock().map_err(|e| e.to_string())?;
+            sessions::fetch_session(&conn, session_id).is_ok_and(|s| s.is_running)
+        };
+        if !still_running {
+            let run = active.take().unwrap();
+            let _ = app.emit("pomodoro-stopped", run.status());
+            return Ok(false);
+        }
+    }
+
+    if Utc::now() >= run.phase_ends_at {
+        if let Err(e) = advance(app, run, true) {
+            // e.g. the session policy refused the next focus session
+            let run = active.take().unwrap();
+            let _ = app.emit("pomodoro-stopped", run.status());
+            return Err(e);
+        }
+    }
+
+    let remaining = (run.phase_ends_at - Utc::now()).num_seconds().max(0);
+    let icon = if run.phase == Phase::Focus { "🍅" } else { "☕" };
+    set_tray_title(app, Some(format!("{} {:02}:{:02}", icon, remaining / 60, remaining % 60)));
+    Ok(true)
+}
+
+/// Manage the pomodoro state and drive it once a second
+pub fn setup_pomodoro<R: Runtime>(app: &tauri::App<R>) {
+    app.manage(PomodoroState::default());
+    let app_handle = app.handle().clone();
+
+    std::thread::spawn(move || {
+        let mut showing_title = false;
+        loop {
+            match tick(&app_handle) {
+                Ok(active) => {
+                    if showing_title && !active {
+                        set_tray_title(&app_handle, None);
+                    }
+                    showing_title = active;
+                }
+                Err(e) => eprintln!("Pomodoro tick failed: {}", e),
+            }
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+    });
+}
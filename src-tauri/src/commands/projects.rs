@@ -1,6 +1,7 @@
+use crate::commands::journal::{self, TableImage};
 use crate::models::Project;
 use chrono::{DateTime, Utc};
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 use std::sync::Mutex;
 use tauri::State;
 use uuid::Uuid;
@@ -8,11 +9,14 @@ use uuid::Uuid;
 #[tauri::command]
 pub fn get_all_projects(db: State<Mutex<Connection>>) -> Result<Vec<Project>, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
+    query_all_projects(&conn)
+}
 
+pub(crate) fn query_all_projects(conn: &Connection) -> Result<Vec<Project>, String> {
     let mut stmt = conn
         .prepare(
             "SELECT id, name, description, client_id, color, priority, status,
-             estimated_hours, hours_per_day, hours_per_week, deadline, created_at, updated_at
+             estimated_hours, hours_per_day, hours_per_week, deadline, hourly_rate, currency, created_at, updated_at
              FROM projects ORDER BY deadline IS NULL, deadline ASC, priority DESC, name ASC"
         )
         .map_err(|e| e.to_string())?;
@@ -31,8 +35,10 @@ pub fn get_all_projects(db: State<Mutex<Connection>>) -> Result<Vec<Project>, St
                 hours_per_day: row.get(8)?,
                 hours_per_week: row.get(9)?,
                 deadline: row.get::<_, Option<String>>(10)?.map(|d| d.parse().unwrap()),
-                created_at: row.get::<_, String>(11)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(12)?.parse().unwrap(),
+                hourly_rate: row.get(11)?,
+                currency: row.get(12)?,
+                created_at: row.get::<_, String>(13)?.parse().unwrap(),
+                updated_at: row.get::<_, String>(14)?.parse().unwrap(),
             })
         })
         .map_err(|e| e.to_string())?
@@ -49,7 +55,7 @@ pub fn get_project(db: State<Mutex<Connection>>, id: String) -> Result<Project,
     let project = conn
         .query_row(
             "SELECT id, name, description, client_id, color, priority, status,
-             estimated_hours, hours_per_day, hours_per_week, deadline, created_at, updated_at
+             estimated_hours, hours_per_day, hours_per_week, deadline, hourly_rate, currency, created_at, updated_at
              FROM projects WHERE id = ?1",
             [&id],
             |row| {
@@ -65,8 +71,10 @@ pub fn get_project(db: State<Mutex<Connection>>, id: String) -> Result<Project,
                     hours_per_day: row.get(8)?,
                     hours_per_week: row.get(9)?,
                     deadline: row.get::<_, Option<String>>(10)?.map(|d| d.parse().unwrap()),
-                    created_at: row.get::<_, String>(11)?.parse().unwrap(),
-                    updated_at: row.get::<_, String>(12)?.parse().unwrap(),
+                    hourly_rate: row.get(11)?,
+                    currency: row.get(12)?,
+                    created_at: row.get::<_, String>(13)?.parse().unwrap(),
+                    updated_at: row.get::<_, String>(14)?.parse().unwrap(),
                 })
             },
         )
@@ -76,6 +84,7 @@ pub fn get_project(db: State<Mutex<Connection>>, id: String) -> Result<Project,
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn create_project(
     db: State<Mutex<Connection>>,
     name: String,
@@ -127,12 +136,15 @@ pub fn create_project(
         hours_per_day,
         hours_per_week,
         deadline,
+        hourly_rate: None,
+        currency: None,
         created_at: now,
         updated_at: now,
     })
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_project(
     db: State<Mutex<Connection>>,
     id: String,
@@ -175,12 +187,73 @@ pub fn update_project(
     get_project(db, id)
 }
 
+/// Override the client's hourly rate and currency for a single project
 #[tauri::command]
-pub fn delete_project(db: State<Mutex<Connection>>, id: String) -> Result<(), String> {
+pub fn update_project_billing(
+    db: State<Mutex<Connection>>,
+    id: String,
+    hourly_rate: Option<f64>,
+    currency: Option<String>,
+) -> Result<Project, String> {
+    if hourly_rate.is_some_and(|rate| rate < 0.0) {
+        return Err("Hourly rate cannot be negative".to_string());
+    }
+
     let conn = db.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    conn.execute(
+        "UPDATE projects SET hourly_rate = ?1, currency = ?2, updated_at = ?3 WHERE id = ?4",
+        rusqlite::params![&hourly_rate, &currency.map(|c| c.to_uppercase()), &now.to_rfc3339(), &id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    drop(conn);
+    get_project(db, id)
+}
 
-    conn.execute("DELETE FROM projects WHERE id = ?1", [&id])
+/// Move a project to the trash together with its tasks and sessions; see `journal`
+/// for undo and restore
+#[tauri::command]
+pub fn delete_project(db: State<Mutex<Connection>>, id: String) -> Result<(), String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    journal::perform(&tx, "delete_project", &id)?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Delete a project and everything hanging off it, returning its name and the
+/// before-images of the removed rows
+pub(crate) fn remove_project(conn: &Connection, id: &str) -> Result<(String, Vec<TableImage>), String> {
+    let name: String = conn
+        .query_row("SELECT name FROM projects WHERE id = ?1", [id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Project not found: {}", id))?;
+
+    let invoiced: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM time_sessions WHERE project_id = ?1 AND invoice_id IS NOT NULL",
+            [id],
+            |row| row.get(0),
+        )
         .map_err(|e| e.to_string())?;
+    if invoiced > 0 {
+        return Err("The project has invoiced sessions; void their invoices first".to_string());
+    }
+
+    const OF_PROJECT_SESSIONS: &str = "session_id IN (SELECT id FROM time_sessions WHERE project_id = ?1)";
+    let images = vec![
+        journal::remove_rows(conn, "session_tags", OF_PROJECT_SESSIONS, id)?,
+        journal::remove_rows(conn, "session_pauses", OF_PROJECT_SESSIONS, id)?,
+        journal::remove_rows(conn, "time_sessions", "project_id = ?1", id)?,
+        journal::remove_rows(conn, "tasks", "project_id = ?1", id)?,
+        journal::remove_rows(conn, "budget_alerts", "project_id = ?1", id)?,
+        journal::remove_rows(conn, "target_alerts", "project_id = ?1", id)?,
+        journal::remove_rows(conn, "projects", "id = ?1", id)?,
+    ];
 
-    Ok(())
+    Ok((name, images))
 }
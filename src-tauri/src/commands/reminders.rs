@@ -0,0 +1,267 @@
+use crate::commands::{pomodoro, sessions, settings};
+use crate::models::{Reminder, TimeSession};
+use crate::{timezone, tray_manager};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+
+/// Seconds between reminder checks
+const CHECK_INTERVAL_SECS: u64 = 60;
+/// Minimum minutes between two "start a timer" reminders
+const IDLE_REPEAT_MINUTES: i64 = 60;
+const DEFAULT_IDLE_MINUTES: f64 = 15.0;
+const DEFAULT_MAX_SESSION_HOURS: f64 = 4.0;
+
+#[derive(Default)]
+struct ReminderTracker {
+    pending: Option<Reminder>,
+    last_idle_reminder: Option<DateTime<Utc>>,
+    flagged_sessions: HashSet<String>, // runaway sessions already reported
+}
+
+/// Reminder bookkeeping. Lock it before the database when both are needed.
+#[derive(Default)]
+pub struct ReminderState(Mutex<ReminderTracker>);
+
+fn read_number(conn: &Connection, key: &str, default: f64) -> Result<f64, String> {
+    Ok(settings::read_setting(conn, key)?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default))
+}
+
+fn project_name(conn: &Connection, project_id: &str) -> Result<String, String> {
+    conn.query_row("SELECT name FROM projects WHERE id = ?1", [project_id], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+/// Project (and task) of the most recent session on an active project
+fn last_project(conn: &Connection) -> Result<Option<(String, String, Option<String>)>, String> {
+    conn.query_row(
+        "SELECT ts.project_id, p.name, ts.task_id
+         FROM time_sessions ts JOIN projects p ON ts.project_id = p.id
+         WHERE p.status = 'active'
+         ORDER BY ts.start_time DESC LIMIT 1",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn running_sessions(conn: &Connection) -> Result<Vec<TimeSession>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM time_sessions WHERE is_running = 1 ORDER BY start_time ASC",
+            sessions::SESSION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let running = stmt
+        .query_map([], sessions::session_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<TimeSession>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(running)
+}
+
+/// The reminder offered in the tray menu, if any
+pub(crate) fn pending_reminder<R: Runtime>(app: &AppHandle<R>) -> Option<Reminder> {
+    app.try_state::<ReminderState>()
+        .and_then(|state| state.0.lock().ok().and_then(|tracker| tracker.pending.clone()))
+}
+
+/// Flag a session running past the maximum, or nudge when nothing has run for a
+/// while during working hours. Desktop notifications cannot carry buttons, so the
+/// follow-up action is offered in the tray menu and through the "reminder" event.
+fn check_reminders<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    // Checked before taking any lock: the pomodoro engine holds its own while it
+    // waits for the database
+    let in_pomodoro = pomodoro::pomodoro_active(app);
+
+    let state = app.state::<ReminderState>();
+    let mut tracker = state.0.lock().map_err(|e| e.to_string())?;
+    let db = app.state::<Mutex<Connection>>();
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let running = running_sessions(&conn)?;
+    let mut changed = false;
+
+    // Drop a pending reminder that no longer applies
+    let stale = match &tracker.pending {
+        Some(reminder) if reminder.kind == "start" => !running.is_empty(),
+        Some(reminder) => !running.iter().any(|s| reminder.session_id.as_ref() == Some(&s.id)),
+        None => false,
+    };
+    if stale {
+        tracker.pending = None;
+        changed = true;
+    }
+    tracker.flagged_sessions.retain(|id| running.iter().any(|s| &s.id == id));
+
+    let mut notification = None;
+
+    // Paused sessions are not running away; pauses do not count towards the maximum
+    let max_hours = read_number(&conn, settings::REMINDER_MAX_SESSION_HOURS, DEFAULT_MAX_SESSION_HOURS)?;
+    let mut runaway = None;
+    if max_hours > 0.0 {
+        for session in running
+            .iter()
+            .filter(|s| s.paused_at.is_none() && !tracker.flagged_sessions.contains(&s.id))
+        {
+            let elapsed = sessions::active_seconds(&conn, &session.id, session.start_time, now)?;
+            if elapsed as f64 >= max_hours * 3600.0 {
+                runaway = Some((session, elapsed));
+                break;
+            }
+        }
+    }
+    if let Some((session, elapsed)) = runaway {
+        let name = project_name(&conn, &session.project_id)?;
+        notification = Some((
+            "¿Sigues trabajando?".to_string(),
+            format!("{} lleva {} h {} min en marcha", name, elapsed / 3600, elapsed / 60 % 60),
+        ));
+        tracker.flagged_sessions.insert(session.id.clone());
+        tracker.pending = Some(Reminder {
+            kind: "stop".to_string(),
+            project_id: session.project_id.clone(),
+            project_name: name,
+            task_id: session.task_id.clone(),
+            session_id: Some(session.id.clone()),
+            created_at: now,
+        });
+        changed = true;
+    }
+
+    let idle_minutes = read_number(&conn, settings::REMINDER_IDLE_MINUTES, DEFAULT_IDLE_MINUTES)?;
+    if notification.is_none() && running.is_empty() && idle_minutes > 0.0 && !in_pomodoro {
+        let tz = timezone::user_timezone(&conn);
+        let local = now.with_timezone(&tz);
+        let start = settings::read_time_setting(&conn, settings::WORKDAY_START, NaiveTime::from_hms_opt(9, 0, 0).unwrap())?;
+        let end = settings::read_time_setting(&conn, settings::WORKDAY_END, NaiveTime::from_hms_opt(18, 0, 0).unwrap())?;
+        let working = settings::read_workdays(&conn)?.contains(&local.weekday()) && local.time() >= start && local.time() < end;
+
+        if working {
+            // Idle since the later of the start of the working day and the last stop
+            let day_start = tz
+                .from_local_datetime(&local.date_naive().and_time(start))
+                .earliest()
+                .map_or(now, |dt| dt.with_timezone(&Utc));
+            let last_stop: Option<DateTime<Utc>> = conn
+                .query_row(
+                    "SELECT MAX(end_time) FROM time_sessions WHERE is_running = 0",
+                    [],
+                    |row| row.get::<_, Option<String>>(0),
+                )
+                .map_err(|e| e.to_string())?
+                .and_then(|end| end.parse().ok());
+            let idle_since = last_stop.map_or(day_start, |stop| stop.max(day_start));
+
+            let idle_long_enough = now - idle_since >= Duration::seconds((idle_minutes * 60.0) as i64);
+            let not_repeated = tracker
+                .last_idle_reminder
+                .map_or(true, |last| now - last >= Duration::minutes(IDLE_REPEAT_MINUTES));
+
+            if idle_long_enough && not_repeated {
+                tracker.last_idle_reminder = Some(now);
+                let body = match last_project(&conn)? {
+                    Some((project_id, name, task_id)) => {
+                        let body = format!("No hay ningún temporizador activo. Último proyecto: {}", name);
+                        tracker.pending = Some(Reminder {
+                            kind: "start".to_string(),
+                            project_id,
+                            project_name: name,
+                            task_id,
+                            session_id: None,
+                            created_at: now,
+                        });
+                        changed = true;
+                        body
+                    }
+                    None => "No hay ningún temporizador activo".to_string(),
+                };
+                notification = Some(("¿Olvidaste iniciar el temporizador?".to_string(), body));
+            }
+        }
+    }
+
+    let pending = tracker.pending.clone();
+    drop(conn);
+    drop(tracker);
+
+    if let Some((title, body)) = notification {
+        if let Err(e) = app.notification().builder().title(title).body(body).show() {
+            eprintln!("Failed to show reminder: {}", e);
+        }
+        let _ = app.emit("reminder", &pending);
+    }
+    if changed {
+        tray_manager::refresh_tray_menu(app);
+    }
+
+    Ok(())
+}
+
+/// Carry out the pending reminder: start its project again or stop the runaway
+/// session (keeping its notes). Returns the affected session.
+pub(crate) fn act_on_pending_reminder<R: Runtime>(app: &AppHandle<R>) -> Result<Option<TimeSession>, String> {
+    let pending = {
+        let state = app.state::<ReminderState>();
+        let mut tracker = state.0.lock().map_err(|e| e.to_string())?;
+        tracker.pending.take()
+    };
+    let Some(reminder) = pending else {
+        return Ok(None);
+    };
+
+    let db = app.state::<Mutex<Connection>>();
+    let result = match (reminder.kind.as_str(), reminder.session_id) {
+        ("stop", Some(session_id)) => {
+            let notes = {
+                let conn = db.lock().map_err(|e| e.to_string())?;
+                sessions::fetch_session(&conn, &session_id)?.notes
+            };
+            sessions::stop_session(db, session_id, notes)
+        }
+        _ => sessions::start_session(db, reminder.project_id, reminder.task_id),
+    };
+
+    tray_manager::refresh_tray_menu(app);
+    result.map(Some)
+}
+
+#[tauri::command]
+pub fn get_pending_reminder(reminders: State<ReminderState>) -> Result<Option<Reminder>, String> {
+    let tracker = reminders.0.lock().map_err(|e| e.to_string())?;
+    Ok(tracker.pending.clone())
+}
+
+#[tauri::command]
+pub fn act_on_reminder<R: Runtime>(app: AppHandle<R>) -> Result<Option<TimeSession>, String> {
+    act_on_pending_reminder(&app)
+}
+
+#[tauri::command]
+pub fn dismiss_reminder<R: Runtime>(app: AppHandle<R>, reminders: State<ReminderState>) -> Result<(), String> {
+    reminders.0.lock().map_err(|e| e.to_string())?.pending = None;
+    tray_manager::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Manage the reminder state and check for reminders every minute
+pub fn setup_reminders<R: Runtime>(app: &tauri::App<R>) {
+    app.manage(ReminderState::default());
+    let app_handle = app.handle().clone();
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        if let Err(e) = check_reminders(&app_handle) {
+            eprintln!("Reminder check failed: {}", e);
+        }
+    });
+}
@@ -1,35 +1,59 @@
-use crate::models::TimeSession;
+use crate::commands::journal::{self, TableImage};
+use crate::commands::settings;
+use crate::models::{OrphanedSession, SessionPause, TimeSession};
+use crate::timezone;
 use chrono::{DateTime, Utc};
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Mutex;
 use tauri::State;
 use uuid::Uuid;
 
+pub(crate) const SESSION_COLUMNS: &str = concat!(
+    "id, project_id, start_time, end_time, duration_seconds, notes, is_running, billable, invoice_id, created_at, updated_at, task_id, timezone, pomodoros, ",
+    "(SELECT paused_at FROM session_pauses WHERE session_id = time_sessions.id AND resumed_at IS NULL), ",
+    "(SELECT COALESCE(SUM(CAST(ROUND((julianday(resumed_at) - julianday(paused_at)) * 86400) AS INTEGER)), 0) ",
+    "FROM session_pauses WHERE session_id = time_sessions.id AND resumed_at IS NOT NULL)"
+);
+
+/// Map a row selected with `SESSION_COLUMNS`
+pub(crate) fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<TimeSession> {
+    Ok(TimeSession {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        start_time: row.get::<_, String>(2)?.parse().unwrap(),
+        end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
+        duration_seconds: row.get(4)?,
+        notes: row.get(5)?,
+        is_running: row.get::<_, i32>(6)? == 1,
+        billable: row.get::<_, i32>(7)? == 1,
+        invoice_id: row.get(8)?,
+        task_id: row.get(11)?,
+        timezone: row.get(12)?,
+        pomodoros: row.get(13)?,
+        paused_at: row.get::<_, Option<String>>(14)?.map(|d| d.parse().unwrap()),
+        paused_seconds: row.get(15)?,
+        created_at: row.get::<_, String>(9)?.parse().unwrap(),
+        updated_at: row.get::<_, String>(10)?.parse().unwrap(),
+    })
+}
+
 #[tauri::command]
 pub fn get_all_sessions(db: State<Mutex<Connection>>) -> Result<Vec<TimeSession>, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
+    query_all_sessions(&conn)
+}
 
+pub(crate) fn query_all_sessions(conn: &Connection) -> Result<Vec<TimeSession>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, project_id, start_time, end_time, duration_seconds, notes, is_running, created_at, updated_at
-             FROM time_sessions ORDER BY start_time DESC"
+            &format!("SELECT {} FROM time_sessions ORDER BY start_time DESC", SESSION_COLUMNS)
         )
         .map_err(|e| e.to_string())?;
 
     let sessions = stmt
-        .query_map([], |row| {
-            Ok(TimeSession {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                start_time: row.get::<_, String>(2)?.parse().unwrap(),
-                end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
-                duration_seconds: row.get(4)?,
-                notes: row.get(5)?,
-                is_running: row.get::<_, i32>(6)? == 1,
-                created_at: row.get::<_, String>(7)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(8)?.parse().unwrap(),
-            })
-        })
+        .query_map([], session_from_row)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<TimeSession>, _>>()
         .map_err(|e| e.to_string())?;
@@ -40,28 +64,18 @@ pub fn get_all_sessions(db: State<Mutex<Connection>>) -> Result<Vec<TimeSession>
 #[tauri::command]
 pub fn get_running_sessions(db: State<Mutex<Connection>>) -> Result<Vec<TimeSession>, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
+    query_running_sessions(&conn)
+}
 
+pub(crate) fn query_running_sessions(conn: &Connection) -> Result<Vec<TimeSession>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, project_id, start_time, end_time, duration_seconds, notes, is_running, created_at, updated_at
-             FROM time_sessions WHERE is_running = 1 ORDER BY start_time DESC"
+            &format!("SELECT {} FROM time_sessions WHERE is_running = 1 ORDER BY start_time DESC", SESSION_COLUMNS)
         )
         .map_err(|e| e.to_string())?;
 
     let sessions = stmt
-        .query_map([], |row| {
-            Ok(TimeSession {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                start_time: row.get::<_, String>(2)?.parse().unwrap(),
-                end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
-                duration_seconds: row.get(4)?,
-                notes: row.get(5)?,
-                is_running: row.get::<_, i32>(6)? == 1,
-                created_at: row.get::<_, String>(7)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(8)?.parse().unwrap(),
-            })
-        })
+        .query_map([], session_from_row)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<TimeSession>, _>>()
         .map_err(|e| e.to_string())?;
@@ -78,25 +92,12 @@ pub fn get_project_sessions(
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, project_id, start_time, end_time, duration_seconds, notes, is_running, created_at, updated_at
-             FROM time_sessions WHERE project_id = ?1 ORDER BY start_time DESC"
+            &format!("SELECT {} FROM time_sessions WHERE project_id = ?1 ORDER BY start_time DESC", SESSION_COLUMNS)
         )
         .map_err(|e| e.to_string())?;
 
     let sessions = stmt
-        .query_map([&project_id], |row| {
-            Ok(TimeSession {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                start_time: row.get::<_, String>(2)?.parse().unwrap(),
-                end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
-                duration_seconds: row.get(4)?,
-                notes: row.get(5)?,
-                is_running: row.get::<_, i32>(6)? == 1,
-                created_at: row.get::<_, String>(7)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(8)?.parse().unwrap(),
-            })
-        })
+        .query_map([&project_id], session_from_row)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<TimeSession>, _>>()
         .map_err(|e| e.to_string())?;
@@ -108,20 +109,104 @@ pub fn get_project_sessions(
 pub fn start_session(
     db: State<Mutex<Connection>>,
     project_id: String,
+    task_id: Option<String>,
 ) -> Result<TimeSession, String> {
-    let conn = db.lock().map_err(|e| e.to_string())?;
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    ensure_can_start(&tx, &project_id)?;
+    let session = insert_running_session(&tx, project_id, task_id, Utc::now())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(session)
+}
+
+/// Stop `from_session_id` and start `to_project_id` at the same instant, in one
+/// transaction, so the running rows never overlap or duplicate.
+#[tauri::command]
+pub fn switch_session(
+    db: State<Mutex<Connection>>,
+    from_session_id: String,
+    to_project_id: String,
+    notes: Option<String>,
+    to_task_id: Option<String>,
+) -> Result<TimeSession, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
     let now = Utc::now();
+
+    let from = fetch_session(&tx, &from_session_id)?;
+    if !from.is_running {
+        return Err("Session is not running".to_string());
+    }
+
+    close_session_at(&tx, &from, now)?;
+    tx.execute(
+        "UPDATE time_sessions SET notes = COALESCE(?1, notes) WHERE id = ?2",
+        rusqlite::params![&notes, &from_session_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    ensure_can_start(&tx, &to_project_id)?;
+    let session = insert_running_session(&tx, to_project_id, to_task_id, now)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(session)
+}
+
+/// Check the configured session policy before a new session is started
+pub(crate) fn ensure_can_start(conn: &Connection, project_id: &str) -> Result<(), String> {
+    let policy = settings::read_setting(conn, settings::SESSION_POLICY)?
+        .unwrap_or_else(|| "parallel".to_string());
+
+    let running_count: i64 = match policy.as_str() {
+        "one_per_project" => conn.query_row(
+            "SELECT COUNT(*) FROM time_sessions WHERE is_running = 1 AND project_id = ?1",
+            [project_id],
+            |row| row.get(0),
+        ),
+        "one_global" => conn.query_row(
+            "SELECT COUNT(*) FROM time_sessions WHERE is_running = 1",
+            [],
+            |row| row.get(0),
+        ),
+        _ => Ok(0),
+    }
+    .map_err(|e| e.to_string())?;
+
+    match (policy.as_str(), running_count) {
+        (_, 0) => Ok(()),
+        ("one_per_project", _) => Err("This project already has a running session".to_string()),
+        _ => Err("Another session is already running; switch to this project instead".to_string()),
+    }
+}
+
+fn insert_running_session(
+    conn: &Connection,
+    project_id: String,
+    task_id: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<TimeSession, String> {
+    if let Some(task_id) = &task_id {
+        ensure_task_in_project(conn, task_id, &project_id)?;
+    }
+
     let id = Uuid::new_v4().to_string();
+    let timezone = timezone::user_timezone(conn).name().to_string();
 
     conn.execute(
-        "INSERT INTO time_sessions (id, project_id, start_time, is_running, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO time_sessions (id, project_id, task_id, timezone, start_time, is_running, last_heartbeat,
+         created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
         rusqlite::params![
             &id,
             &project_id,
+            &task_id,
+            &timezone,
             &now.to_rfc3339(),
             1,
             &now.to_rfc3339(),
+            &now.to_rfc3339(),
             &now.to_rfc3339()
         ],
     )
@@ -135,6 +220,13 @@ pub fn start_session(
         duration_seconds: None,
         notes: None,
         is_running: true,
+        billable: true,
+        invoice_id: None,
+        task_id,
+        timezone: Some(timezone),
+        pomodoros: 0,
+        paused_at: None,
+        paused_seconds: 0,
         created_at: now,
         updated_at: now,
     })
@@ -146,56 +238,170 @@ pub fn stop_session(
     session_id: String,
     notes: Option<String>,
 ) -> Result<TimeSession, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let session = fetch_session(&tx, &session_id)?;
+    if !session.is_running {
+        return Err("Session is not running".to_string());
+    }
+    ensure_not_invoiced(&tx, &session_id)?;
+
+    close_session_at(&tx, &session, Utc::now())?;
+    // Stopping without notes (e.g. from the tray) keeps the ones already there
+    tx.execute(
+        "UPDATE time_sessions SET notes = COALESCE(?1, notes) WHERE id = ?2",
+        rusqlite::params![&notes, &session_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let session = fetch_session(&tx, &session_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(session)
+}
+
+/// Pause a running session; paused time is left out of its duration
+#[tauri::command]
+pub fn pause_session(db: State<Mutex<Connection>>, session_id: String) -> Result<TimeSession, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
-    let now = Utc::now();
 
-    // Get the session to calculate duration
-    let session = conn
-        .query_row(
-            "SELECT start_time FROM time_sessions WHERE id = ?1",
-            [&session_id],
-            |row| {
-                let start_time_str: String = row.get(0)?;
-                let start_time: DateTime<Utc> = start_time_str.parse().unwrap();
-                Ok(start_time)
-            },
+    let session = fetch_session(&conn, &session_id)?;
+    if !session.is_running {
+        return Err("Session is not running".to_string());
+    }
+    if session.paused_at.is_some() {
+        return Err("Session is already paused".to_string());
+    }
+
+    insert_pause(&conn, &session_id, Utc::now(), "manual")?;
+    fetch_session(&conn, &session_id)
+}
+
+#[tauri::command]
+pub fn resume_session(db: State<Mutex<Connection>>, session_id: String) -> Result<TimeSession, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let session = fetch_session(&conn, &session_id)?;
+    if session.paused_at.is_none() {
+        return Err("Session is not paused".to_string());
+    }
+
+    end_open_pauses(&conn, &session_id, Utc::now())?;
+    fetch_session(&conn, &session_id)
+}
+
+#[tauri::command]
+pub fn get_session_pauses(db: State<Mutex<Connection>>, session_id: String) -> Result<Vec<SessionPause>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    query_session_pauses(&conn, Some(&session_id))
+}
+
+/// Pauses of one session, or of every session with `None`, oldest first
+pub(crate) fn query_session_pauses(conn: &Connection, session_id: Option<&str>) -> Result<Vec<SessionPause>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, session_id, paused_at, resumed_at, reason FROM session_pauses
+             WHERE ?1 IS NULL OR session_id = ?1 ORDER BY paused_at ASC",
         )
         .map_err(|e| e.to_string())?;
 
-    let duration_seconds = (now - session).num_seconds();
+    let pauses = stmt
+        .query_map([session_id], |row| {
+            Ok(SessionPause {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                paused_at: row.get::<_, String>(2)?.parse().unwrap(),
+                resumed_at: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
+                reason: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<SessionPause>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(pauses)
+}
+
+/// Every pause, grouped by session
+pub(crate) fn pauses_by_session(conn: &Connection) -> Result<HashMap<String, Vec<SessionPause>>, String> {
+    let mut grouped: HashMap<String, Vec<SessionPause>> = HashMap::new();
+    for pause in query_session_pauses(conn, None)? {
+        grouped.entry(pause.session_id.clone()).or_default().push(pause);
+    }
+    Ok(grouped)
+}
 
+fn insert_pause(conn: &Connection, session_id: &str, at: DateTime<Utc>, reason: &str) -> Result<(), String> {
     conn.execute(
-        "UPDATE time_sessions SET end_time = ?1, duration_seconds = ?2, notes = ?3, is_running = 0, updated_at = ?4 WHERE id = ?5",
-        rusqlite::params![
-            &now.to_rfc3339(),
-            &duration_seconds,
-            &notes,
-            &now.to_rfc3339(),
-            &session_id
-        ],
+        "INSERT INTO session_pauses (id, session_id, paused_at, reason) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![&Uuid::new_v4().to_string(), session_id, &at.to_rfc3339(), reason],
     )
     .map_err(|e| e.to_string())?;
+    Ok(())
+}
 
-    // Return the updated session
-    conn.query_row(
-        "SELECT id, project_id, start_time, end_time, duration_seconds, notes, is_running, created_at, updated_at
-         FROM time_sessions WHERE id = ?1",
-        [&session_id],
-        |row| {
-            Ok(TimeSession {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                start_time: row.get::<_, String>(2)?.parse().unwrap(),
-                end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
-                duration_seconds: row.get(4)?,
-                notes: row.get(5)?,
-                is_running: row.get::<_, i32>(6)? == 1,
-                created_at: row.get::<_, String>(7)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(8)?.parse().unwrap(),
-            })
-        },
+fn end_open_pauses(conn: &Connection, session_id: &str, at: DateTime<Utc>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE session_pauses SET resumed_at = ?1 WHERE session_id = ?2 AND resumed_at IS NULL",
+        rusqlite::params![&at.to_rfc3339(), session_id],
     )
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Pause every running session that is not paused yet, for the macOS screen lock watcher
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn pause_running_sessions(conn: &Connection, reason: &str) -> Result<Vec<TimeSession>, String> {
+    let now = Utc::now();
+    let mut paused = Vec::new();
+
+    for session in query_running_sessions(conn)?.into_iter().filter(|s| s.paused_at.is_none()) {
+        insert_pause(conn, &session.id, now, reason)?;
+        paused.push(fetch_session(conn, &session.id)?);
+    }
+
+    Ok(paused)
+}
+
+/// Resume the sessions paused for `reason`, leaving manual pauses alone
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn resume_paused_sessions(conn: &Connection, reason: &str) -> Result<Vec<TimeSession>, String> {
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT session_id FROM session_pauses WHERE resumed_at IS NULL AND reason = ?1")
+        .map_err(|e| e.to_string())?;
+    let session_ids = stmt
+        .query_map([reason], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let now = Utc::now();
+    let mut resumed = Vec::new();
+    for session_id in session_ids {
+        end_open_pauses(conn, &session_id, now)?;
+        resumed.push(fetch_session(conn, &session_id)?);
+    }
+
+    Ok(resumed)
+}
+
+/// Seconds of `start..end` not covered by the session's pauses; a pause still on
+/// counts until `end`
+pub(crate) fn active_seconds(
+    conn: &Connection,
+    session_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<i64, String> {
+    let paused: i64 = query_session_pauses(conn, Some(session_id))?
+        .iter()
+        .map(|pause| {
+            let resumed_at = pause.resumed_at.unwrap_or(end);
+            (resumed_at.min(end) - pause.paused_at.max(start)).num_seconds().max(0)
+        })
+        .sum();
+
+    Ok(((end - start).num_seconds() - paused).max(0))
 }
 
 #[tauri::command]
@@ -205,6 +411,7 @@ pub fn update_session_notes(
     notes: Option<String>,
 ) -> Result<TimeSession, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
+    ensure_not_invoiced(&conn, &session_id)?;
     let now = Utc::now();
 
     conn.execute(
@@ -214,22 +421,9 @@ pub fn update_session_notes(
     .map_err(|e| e.to_string())?;
 
     conn.query_row(
-        "SELECT id, project_id, start_time, end_time, duration_seconds, notes, is_running, created_at, updated_at
-         FROM time_sessions WHERE id = ?1",
+        &format!("SELECT {} FROM time_sessions WHERE id = ?1", SESSION_COLUMNS),
         [&session_id],
-        |row| {
-            Ok(TimeSession {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                start_time: row.get::<_, String>(2)?.parse().unwrap(),
-                end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
-                duration_seconds: row.get(4)?,
-                notes: row.get(5)?,
-                is_running: row.get::<_, i32>(6)? == 1,
-                created_at: row.get::<_, String>(7)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(8)?.parse().unwrap(),
-            })
-        },
+        session_from_row,
     )
     .map_err(|e| e.to_string())
 }
@@ -242,8 +436,10 @@ pub fn update_session(
     start_time: DateTime<Utc>,
     end_time: DateTime<Utc>,
     notes: Option<String>,
+    billable: Option<bool>,
 ) -> Result<TimeSession, String> {
-    let conn = db.lock().map_err(|e| e.to_string())?;
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    ensure_not_invoiced(&conn, &session_id)?;
     let now = Utc::now();
 
     // Calculate duration from start_time and end_time
@@ -253,122 +449,1144 @@ pub fn update_session(
     if duration_seconds < 0 {
         return Err("End time must be after start time".to_string());
     }
+    if find_overlapping_session(&conn, &project_id, start_time, end_time, Some(&session_id))?.is_some() {
+        return Err("The session would overlap another session of the project".to_string());
+    }
 
-    conn.execute(
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    // Pauses left outside the new span no longer apply
+    tx.execute(
+        "DELETE FROM session_pauses WHERE session_id = ?1 AND resumed_at IS NOT NULL
+         AND (paused_at >= ?2 OR resumed_at <= ?3)",
+        rusqlite::params![&session_id, &end_time.to_rfc3339(), &start_time.to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+    let duration_seconds = active_seconds(&tx, &session_id, start_time, end_time)?;
+
+    // A task only makes sense within its project, so moving the session unlinks it
+    tx.execute(
         "UPDATE time_sessions SET project_id = ?1, start_time = ?2, end_time = ?3,
-         duration_seconds = ?4, notes = ?5, updated_at = ?6 WHERE id = ?7",
+         duration_seconds = ?4, notes = ?5, billable = COALESCE(?6, billable),
+         task_id = CASE WHEN project_id = ?1 THEN task_id ELSE NULL END, updated_at = ?7 WHERE id = ?8",
         rusqlite::params![
             &project_id,
             &start_time.to_rfc3339(),
             &end_time.to_rfc3339(),
             &duration_seconds,
             &notes,
+            &billable,
             &now.to_rfc3339(),
             &session_id
         ],
     )
     .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
 
     // Return the updated session
     conn.query_row(
-        "SELECT id, project_id, start_time, end_time, duration_seconds, notes, is_running, created_at, updated_at
-         FROM time_sessions WHERE id = ?1",
+        &format!("SELECT {} FROM time_sessions WHERE id = ?1", SESSION_COLUMNS),
         [&session_id],
-        |row| {
-            Ok(TimeSession {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                start_time: row.get::<_, String>(2)?.parse().unwrap(),
-                end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
-                duration_seconds: row.get(4)?,
-                notes: row.get(5)?,
-                is_running: row.get::<_, i32>(6)? == 1,
-                created_at: row.get::<_, String>(7)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(8)?.parse().unwrap(),
-            })
-        },
+        session_from_row,
     )
     .map_err(|e| e.to_string())
 }
 
+/// A finished session entered by hand
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManualSessionEntry {
+    pub project_id: String,
+    pub task_id: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub notes: Option<String>,
+    pub billable: Option<bool>,
+    pub tag_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkSessionError {
+    pub index: usize, // position in the submitted list, 0-based
+    pub message: String,
+}
+
+/// Outcome of `create_manual_sessions`: either every entry was created, or
+/// nothing was and `errors` lists each rejected entry
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkSessionReport {
+    pub created: Vec<TimeSession>,
+    pub errors: Vec<BulkSessionError>,
+}
+
+/// A session of `project_id` sharing time with `start..end`, other than `exclude_id`.
+/// Running sessions count as lasting until now.
+pub(crate) fn find_overlapping_session(
+    conn: &Connection,
+    project_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    exclude_id: Option<&str>,
+) -> Result<Option<TimeSession>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM time_sessions
+             WHERE project_id = ?1 AND start_time < ?2
+             AND COALESCE(CASE WHEN is_running = 1 THEN ?4 ELSE end_time END, ?4) > ?3
+             AND (?5 IS NULL OR id != ?5)
+             ORDER BY start_time ASC LIMIT 1",
+            SESSION_COLUMNS
+        ),
+        rusqlite::params![
+            project_id,
+            &end.to_rfc3339(),
+            &start.to_rfc3339(),
+            &Utc::now().to_rfc3339(),
+            exclude_id
+        ],
+        session_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn insert_manual_session(conn: &Connection, entry: ManualSessionEntry) -> Result<TimeSession, String> {
+    let now = Utc::now();
+    if entry.end_time <= entry.start_time {
+        return Err("End time must be after start time".to_string());
+    }
+    if entry.end_time > now {
+        return Err("A manual session cannot end in the future".to_string());
+    }
+
+    let project_exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM projects WHERE id = ?1", [&entry.project_id], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map_err(|e| e.to_string())?
+        > 0;
+    if !project_exists {
+        return Err(format!("Project not found: {}", entry.project_id));
+    }
+    if let Some(task_id) = &entry.task_id {
+        ensure_task_in_project(conn, task_id, &entry.project_id)?;
+    }
+
+    let tz = timezone::user_timezone(conn);
+    if let Some(existing) = find_overlapping_session(conn, &entry.project_id, entry.start_time, entry.end_time, None)? {
+        let local = |instant: DateTime<Utc>| instant.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string();
+        return Err(format!(
+            "Overlaps an existing session of the project ({} – {})",
+            local(existing.start_time),
+            existing.end_time.map_or_else(|| "running".to_string(), local)
+        ));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let duration_seconds = (entry.end_time - entry.start_time).num_seconds();
+    conn.execute(
+        "INSERT INTO time_sessions (id, project_id, task_id, timezone, start_time, end_time, duration_seconds,
+         notes, is_running, billable, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?10, ?11)",
+        rusqlite::params![
+            &id,
+            &entry.project_id,
+            &entry.task_id,
+            tz.name(),
+            &entry.start_time.to_rfc3339(),
+            &entry.end_time.to_rfc3339(),
+            &duration_seconds,
+            &entry.notes,
+            entry.billable.unwrap_or(true),
+            &now.to_rfc3339(),
+            &now.to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut tag_ids = entry.tag_ids.unwrap_or_default();
+    tag_ids.sort();
+    tag_ids.dedup();
+    for tag_id in &tag_ids {
+        let inserted = conn
+            .execute(
+                "INSERT INTO session_tags (session_id, tag_id) SELECT ?1, id FROM tags WHERE id = ?2",
+                [&id, tag_id],
+            )
+            .map_err(|e| e.to_string())?;
+        if inserted == 0 {
+            return Err(format!("Tag not found: {}", tag_id));
+        }
+    }
+
+    fetch_session(conn, &id)
+}
+
+/// Record a finished session after the fact. Rejected when it overlaps another
+/// session of the same project.
+#[tauri::command]
+pub fn create_manual_session(
+    db: State<Mutex<Connection>>,
+    entry: ManualSessionEntry,
+) -> Result<TimeSession, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let session = insert_manual_session(&tx, entry)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(session)
+}
+
+/// Create many finished sessions at once (e.g. from a week grid) in one transaction.
+/// Entries are checked in order, so they may not overlap each other either. If any
+/// entry is rejected nothing is saved and every failing entry is reported.
+#[tauri::command]
+pub fn create_manual_sessions(
+    db: State<Mutex<Connection>>,
+    entries: Vec<ManualSessionEntry>,
+) -> Result<BulkSessionReport, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut report = BulkSessionReport {
+        created: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        // A savepoint per entry keeps a half-inserted entry (e.g. a bad tag) out
+        tx.execute_batch("SAVEPOINT manual_entry").map_err(|e| e.to_string())?;
+        match insert_manual_session(&tx, entry) {
+            Ok(session) => {
+                tx.execute_batch("RELEASE manual_entry").map_err(|e| e.to_string())?;
+                report.created.push(session);
+            }
+            Err(message) => {
+                tx.execute_batch("ROLLBACK TO manual_entry; RELEASE manual_entry")
+                    .map_err(|e| e.to_string())?;
+                report.errors.push(BulkSessionError { index, message });
+            }
+        }
+    }
+
+    if report.errors.is_empty() {
+        tx.commit().map_err(|e| e.to_string())?;
+    } else {
+        report.created.clear();
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn set_session_billable(
+    db: State<Mutex<Connection>>,
+    session_id: String,
+    billable: bool,
+) -> Result<TimeSession, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    ensure_not_invoiced(&conn, &session_id)?;
+
+    conn.execute(
+        "UPDATE time_sessions SET billable = ?1, updated_at = ?2 WHERE id = ?3",
+        rusqlite::params![&billable, &Utc::now().to_rfc3339(), &session_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_session(&conn, &session_id)
+}
+
+/// Link a session to a task of its project, or unlink it with `None`
+#[tauri::command]
+pub fn set_session_task(
+    db: State<Mutex<Connection>>,
+    session_id: String,
+    task_id: Option<String>,
+) -> Result<TimeSession, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    ensure_not_invoiced(&conn, &session_id)?;
+
+    let session = fetch_session(&conn, &session_id)?;
+    if let Some(task_id) = &task_id {
+        ensure_task_in_project(&conn, task_id, &session.project_id)?;
+    }
+
+    conn.execute(
+        "UPDATE time_sessions SET task_id = ?1, updated_at = ?2 WHERE id = ?3",
+        rusqlite::params![&task_id, &Utc::now().to_rfc3339(), &session_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_session(&conn, &session_id)
+}
+
 #[tauri::command]
 pub fn stop_all_running_sessions(db: State<Mutex<Connection>>) -> Result<Vec<TimeSession>, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
     let now = Utc::now();
 
-    // Get all running sessions
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, project_id, start_time FROM time_sessions WHERE is_running = 1"
-        )
-        .map_err(|e| e.to_string())?;
+    let mut stopped_sessions = Vec::new();
+    for session in query_running_sessions(&conn)? {
+        close_session_at(&conn, &session, now)?;
+        stopped_sessions.push(fetch_session(&conn, &session.id)?);
+    }
 
-    let running_sessions: Vec<(String, String, String)> = stmt
-        .query_map([], |row| {
-            Ok((
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-            ))
-        })
+    Ok(stopped_sessions)
+}
+
+/// Move a session to the trash; see `journal` for undo and restore
+#[tauri::command]
+pub fn delete_session(db: State<Mutex<Connection>>, session_id: String) -> Result<(), String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    journal::perform(&tx, "delete_session", &session_id)?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Delete a session with its tags and pauses, returning a label and their before-images
+pub(crate) fn remove_session(conn: &Connection, session_id: &str) -> Result<(String, Vec<TableImage>), String> {
+    ensure_not_invoiced(conn, session_id)?;
+    let session = fetch_session(conn, session_id)?;
+
+    let project_name: String = conn
+        .query_row("SELECT name FROM projects WHERE id = ?1", [&session.project_id], |row| row.get(0))
+        .optional()
         .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        .unwrap_or_default();
+    let tz = timezone::session_timezone(session.timezone.as_deref(), timezone::user_timezone(conn));
+    let label = format!(
+        "{} {}",
+        project_name,
+        session.start_time.with_timezone(&tz).format("%Y-%m-%d %H:%M")
+    );
 
-    drop(stmt);
+    let images = vec![
+        journal::remove_rows(conn, "session_tags", "session_id = ?1", session_id)?,
+        journal::remove_rows(conn, "session_pauses", "session_id = ?1", session_id)?,
+        journal::remove_rows(conn, "time_sessions", "id = ?1", session_id)?,
+    ];
 
-    // Stop each session
-    let mut stopped_sessions = Vec::new();
-    for (session_id, _project_id, start_time_str) in running_sessions {
-        let start_time: DateTime<Utc> = start_time_str.parse().map_err(|e| format!("Parse error: {}", e))?;
-        let duration_seconds = (now - start_time).num_seconds();
+    Ok((label, images))
+}
+
+/// Fold `absorb` into `keep`: the kept session spans from the earlier start to the
+/// later end (still running if either was) but its duration only counts the time the
+/// two covered; a gap between them is recorded as a "merge_gap" pause so it stays out
+/// of day splits and running totals too. Notes are joined, tags combined, a missing
+/// task taken over, pomodoros added up and pauses moved over. `absorb` is then deleted.
+pub(crate) fn merge_session_into(conn: &Connection, keep: &TimeSession, absorb: &TimeSession) -> Result<(), String> {
+    if keep.project_id != absorb.project_id {
+        return Err("Only sessions of the same project can be merged".to_string());
+    }
+
+    let now = Utc::now();
+    let start = keep.start_time.min(absorb.start_time);
+    let running = keep.is_running || absorb.is_running;
+    let end = if running { None } else { keep.end_time.max(absorb.end_time) };
+    let duration_seconds = match (keep.end_time, absorb.end_time) {
+        (Some(keep_end), Some(absorb_end)) if !running => {
+            let shared = (keep_end.min(absorb_end) - keep.start_time.max(absorb.start_time))
+                .num_seconds()
+                .max(0);
+            Some(keep.duration_seconds.unwrap_or(0) + absorb.duration_seconds.unwrap_or(0) - shared)
+        }
+        _ => None,
+    };
+
+    let keep_notes = keep.notes.as_deref().filter(|n| !n.trim().is_empty());
+    let absorb_notes = absorb.notes.as_deref().filter(|n| !n.trim().is_empty());
+    let notes = match (keep_notes, absorb_notes) {
+        (Some(a), Some(b)) if a != b => Some(format!("{}\n{}", a, b)),
+        (a, b) => a.or(b).map(String::from),
+    };
 
+    conn.execute(
+        "UPDATE time_sessions SET start_time = ?1, end_time = ?2, duration_seconds = ?3, notes = ?4,
+         is_running = ?5, task_id = COALESCE(task_id, ?6), pomodoros = pomodoros + ?7,
+         last_heartbeat = CASE WHEN ?5 = 1 THEN ?8 ELSE last_heartbeat END, updated_at = ?8
+         WHERE id = ?9",
+        rusqlite::params![
+            &start.to_rfc3339(),
+            &end.map(|e| e.to_rfc3339()),
+            &duration_seconds,
+            &notes,
+            running,
+            &absorb.task_id,
+            absorb.pomodoros,
+            &now.to_rfc3339(),
+            &keep.id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO session_tags (session_id, tag_id)
+         SELECT ?1, tag_id FROM session_tags WHERE session_id = ?2",
+        [&keep.id, &absorb.id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM session_tags WHERE session_id = ?1", [&absorb.id])
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE session_pauses SET session_id = ?1 WHERE session_id = ?2",
+        [&keep.id, &absorb.id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let (earlier, later) = if keep.start_time <= absorb.start_time { (keep, absorb) } else { (absorb, keep) };
+    if let Some(gap_start) = earlier.end_time.filter(|end| !earlier.is_running && *end < later.start_time) {
         conn.execute(
-            "UPDATE time_sessions SET end_time = ?1, duration_seconds = ?2, is_running = 0,
-             notes = COALESCE(notes, '') || ' [Auto-pausado]', updated_at = ?3 WHERE id = ?4",
+            "INSERT INTO session_pauses (id, session_id, paused_at, resumed_at, reason)
+             VALUES (?1, ?2, ?3, ?4, 'merge_gap')",
             rusqlite::params![
-                &now.to_rfc3339(),
-                &duration_seconds,
-                &now.to_rfc3339(),
-                &session_id
+                &Uuid::new_v4().to_string(),
+                &keep.id,
+                &gap_start.to_rfc3339(),
+                &later.start_time.to_rfc3339()
             ],
         )
         .map_err(|e| e.to_string())?;
+    }
+
+    conn.execute("DELETE FROM time_sessions WHERE id = ?1", [&absorb.id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Split a session at `at`: the first part keeps the session, the second becomes a
+/// new session from `at` on, optionally for another project (the task link is
+/// dropped then). Both parts keep the notes and tags; a running session keeps
+/// running, and a paused one stays paused, in the second part. Returns both parts.
+#[tauri::command]
+pub fn split_session(
+    db: State<Mutex<Connection>>,
+    session_id: String,
+    at: DateTime<Utc>,
+    new_project_id: Option<String>,
+) -> Result<Vec<TimeSession>, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    ensure_not_invoiced(&tx, &session_id)?;
+    let session = fetch_session(&tx, &session_id)?;
+    let end = if session.is_running { now } else { session.end_time.unwrap_or(session.start_time) };
+    if at <= session.start_time || at >= end {
+        return Err("The split point must fall inside the session".to_string());
+    }
+
+    let project_id = new_project_id.unwrap_or_else(|| session.project_id.clone());
+    let new_id = split_session_at(&tx, &session, at, &project_id, now)?;
+
+    let parts = vec![fetch_session(&tx, &session.id)?, fetch_session(&tx, &new_id)?];
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(parts)
+}
+
+/// The work of `split_session` once `at` is known to fall inside the session;
+/// returns the id of the second part. Each part's duration is its own active time,
+/// so pauses count against the part they fall in.
+pub(crate) fn split_session_at(
+    conn: &Connection,
+    session: &TimeSession,
+    at: DateTime<Utc>,
+    project_id: &str,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    let task_id = if project_id == session.project_id { session.task_id.clone() } else { None };
+    let project_exists: i64 = conn
+        .query_row("SELECT COUNT(*) FROM projects WHERE id = ?1", [project_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if project_exists == 0 {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    let new_id = Uuid::new_v4().to_string();
+    let second_end = session.end_time.filter(|_| !session.is_running);
+    conn.execute(
+        "INSERT INTO time_sessions (id, project_id, task_id, timezone, start_time, end_time,
+         notes, is_running, billable, last_heartbeat, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11)",
+        rusqlite::params![
+            &new_id,
+            project_id,
+            &task_id,
+            &session.timezone,
+            &at.to_rfc3339(),
+            &second_end.map(|e| e.to_rfc3339()),
+            &session.notes,
+            session.is_running,
+            session.billable,
+            session.is_running.then(|| now.to_rfc3339()),
+            &now.to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO session_tags (session_id, tag_id) SELECT ?1, tag_id FROM session_tags WHERE session_id = ?2",
+        [&new_id, &session.id],
+    )
+    .map_err(|e| e.to_string())?;
+    move_pauses_after(conn, &session.id, &new_id, at)?;
 
-        // Get the updated session
-        let session = conn
-            .query_row(
-                "SELECT id, project_id, start_time, end_time, duration_seconds, notes, is_running, created_at, updated_at
-                 FROM time_sessions WHERE id = ?1",
-                [&session_id],
-                |row| {
-                    Ok(TimeSession {
-                        id: row.get(0)?,
-                        project_id: row.get(1)?,
-                        start_time: row.get::<_, String>(2)?.parse().unwrap(),
-                        end_time: row.get::<_, Option<String>>(3)?.map(|d| d.parse().unwrap()),
-                        duration_seconds: row.get(4)?,
-                        notes: row.get(5)?,
-                        is_running: row.get::<_, i32>(6)? == 1,
-                        created_at: row.get::<_, String>(7)?.parse().unwrap(),
-                        updated_at: row.get::<_, String>(8)?.parse().unwrap(),
-                    })
-                },
+    // A running second part gets its duration when it is stopped
+    let first_seconds = active_seconds(conn, &session.id, session.start_time, at)?;
+    let second_seconds = second_end
+        .map(|end| active_seconds(conn, &new_id, at, end))
+        .transpose()?;
+
+    conn.execute(
+        "UPDATE time_sessions SET end_time = ?1, duration_seconds = ?2, is_running = 0, updated_at = ?3
+         WHERE id = ?4",
+        rusqlite::params![&at.to_rfc3339(), &first_seconds, &now.to_rfc3339(), &session.id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE time_sessions SET duration_seconds = ?1 WHERE id = ?2",
+        rusqlite::params![&second_seconds, &new_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(new_id)
+}
+
+/// Merge sessions of one project into the earliest of them. See `merge_session_into`
+/// for how notes, tags, task links and the duration are combined.
+#[tauri::command]
+pub fn merge_sessions(db: State<Mutex<Connection>>, session_ids: Vec<String>) -> Result<TimeSession, String> {
+    let mut session_ids = session_ids;
+    session_ids.sort();
+    session_ids.dedup();
+    if session_ids.len() < 2 {
+        return Err("Choose at least two sessions to merge".to_string());
+    }
+
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut sessions = Vec::new();
+    for id in &session_ids {
+        ensure_not_invoiced(&tx, id)?;
+        sessions.push(fetch_session(&tx, id)?);
+    }
+    sessions.sort_by_key(|s| s.start_time);
+
+    let keep_id = sessions[0].id.clone();
+    for absorb in &sessions[1..] {
+        let keep = fetch_session(&tx, &keep_id)?;
+        merge_session_into(&tx, &keep, absorb)?;
+    }
+
+    let merged = fetch_session(&tx, &keep_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(merged)
+}
+
+/// Hand the pauses after `at` over to another session, cutting one that spans `at`
+/// in two
+fn move_pauses_after(conn: &Connection, from_id: &str, to_id: &str, at: DateTime<Utc>) -> Result<(), String> {
+    for pause in query_session_pauses(conn, Some(from_id))? {
+        if pause.paused_at >= at {
+            conn.execute("UPDATE session_pauses SET session_id = ?1 WHERE id = ?2", [to_id, &pause.id])
+                .map_err(|e| e.to_string())?;
+        } else if pause.resumed_at.map_or(true, |resumed_at| resumed_at > at) {
+            conn.execute(
+                "UPDATE session_pauses SET resumed_at = ?1 WHERE id = ?2",
+                rusqlite::params![&at.to_rfc3339(), &pause.id],
+            )
+            .map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT INTO session_pauses (id, session_id, paused_at, resumed_at, reason) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    &Uuid::new_v4().to_string(),
+                    to_id,
+                    &at.to_rfc3339(),
+                    &pause.resumed_at.map(|r| r.to_rfc3339()),
+                    &pause.reason
+                ],
             )
             .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn fetch_session(conn: &Connection, session_id: &str) -> Result<TimeSession, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM time_sessions WHERE id = ?1", SESSION_COLUMNS),
+        [session_id],
+        session_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
 
-        stopped_sessions.push(session);
+/// Invoiced sessions are frozen until their invoice is voided
+pub(crate) fn ensure_not_invoiced(conn: &Connection, session_id: &str) -> Result<(), String> {
+    let invoice_number: Option<String> = conn
+        .query_row(
+            "SELECT i.number FROM time_sessions ts JOIN invoices i ON ts.invoice_id = i.id WHERE ts.id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match invoice_number {
+        Some(number) => Err(format!("Session is locked by invoice {}", number)),
+        None => Ok(()),
     }
+}
 
-    Ok(stopped_sessions)
+/// Tasks must belong to the session's project
+pub(crate) fn ensure_task_in_project(conn: &Connection, task_id: &str, project_id: &str) -> Result<(), String> {
+    let task_project: Option<String> = conn
+        .query_row("SELECT project_id FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match task_project {
+        Some(id) if id == project_id => Ok(()),
+        Some(_) => Err("Task belongs to a different project".to_string()),
+        None => Err("Task not found".to_string()),
+    }
 }
 
+/// Resolve an idle span reported by the system event watcher.
+/// `action` is "keep" (leave the session as is), "discard" (cut the span out of the
+/// session) or "reassign" (cut it out and book it to `project_id` instead).
 #[tauri::command]
-pub fn delete_session(db: State<Mutex<Connection>>, session_id: String) -> Result<(), String> {
-    let conn = db.lock().map_err(|e| e.to_string())?;
+pub fn resolve_idle_time(
+    db: State<Mutex<Connection>>,
+    session_id: String,
+    idle_start: DateTime<Utc>,
+    idle_end: DateTime<Utc>,
+    action: String,
+    project_id: Option<String>,
+) -> Result<Vec<TimeSession>, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let affected = resolve_idle_span(&tx, &session_id, idle_start, idle_end, &action, project_id.as_deref(), Utc::now())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(affected)
+}
 
-    conn.execute("DELETE FROM time_sessions WHERE id = ?1", [&session_id])
+/// The work of `resolve_idle_time`. For "discard" and "reassign" the session ends
+/// where the idle span begins; what came after the span, if anything, continues as
+/// a split-off session with the same notes, task, billable flag and tags (still
+/// running if the session was, so the number of running sessions never grows). The
+/// span itself goes to `project_id` through the manual entry checks. Returns the
+/// sessions touched.
+pub(crate) fn resolve_idle_span(
+    conn: &Connection,
+    session_id: &str,
+    idle_start: DateTime<Utc>,
+    idle_end: DateTime<Utc>,
+    action: &str,
+    project_id: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Vec<TimeSession>, String> {
+    let reassign_to = match action {
+        "keep" => return Ok(vec![fetch_session(conn, session_id)?]),
+        "discard" => None,
+        "reassign" => Some(project_id.ok_or("A project is required to reassign idle time")?),
+        other => return Err(format!("Unknown idle action: {}", other)),
+    };
+
+    let idle_end = idle_end.min(now);
+    if idle_end <= idle_start {
+        return Err("Idle end must be after idle start".to_string());
+    }
+
+    ensure_not_invoiced(conn, session_id)?;
+    let session = fetch_session(conn, session_id)?;
+    let session_end = if session.is_running { now } else { session.end_time.unwrap_or(session.start_time) };
+    if idle_start < session.start_time || idle_end > session_end {
+        return Err("Idle span is outside the session".to_string());
+    }
+
+    if let Some(project_id) = reassign_to {
+        let status: String = conn
+            .query_row("SELECT status FROM projects WHERE id = ?1", [project_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Project not found: {}", project_id))?;
+        if status != "active" {
+            return Err("Idle time can only be reassigned to an active project".to_string());
+        }
+    }
+
+    let mut affected_ids = vec![session_id.to_string()];
+    if idle_end < session_end {
+        affected_ids.push(split_session_at(conn, &session, idle_end, &session.project_id, now)?);
+    }
+
+    end_session_at(conn, &session, idle_start, now)?;
+
+    if let Some(project_id) = reassign_to {
+        let reassigned = insert_manual_session(
+            conn,
+            ManualSessionEntry {
+                project_id: project_id.to_string(),
+                task_id: session.task_id.clone().filter(|_| project_id == session.project_id),
+                start_time: idle_start,
+                end_time: idle_end,
+                notes: session.notes.clone(),
+                billable: Some(session.billable),
+                tag_ids: Some(session_tag_ids(conn, session_id)?),
+            },
+        )?;
+        affected_ids.push(reassigned.id);
+    }
+
+    affected_ids.iter().map(|id| fetch_session(conn, id)).collect()
+}
+
+/// Finish `session` at `at`, an instant within it: pauses from then on are dropped,
+/// one under way is cut short, and the duration counts up to `at`.
+pub(crate) fn end_session_at(
+    conn: &Connection,
+    session: &TimeSession,
+    at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM session_pauses WHERE session_id = ?1 AND paused_at >= ?2",
+        rusqlite::params![&session.id, &at.to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE session_pauses SET resumed_at = ?1
+         WHERE session_id = ?2 AND (resumed_at IS NULL OR resumed_at > ?1)",
+        rusqlite::params![&at.to_rfc3339(), &session.id],
+    )
+    .map_err(|e| e.to_string())?;
+    let duration_seconds = active_seconds(conn, &session.id, session.start_time, at)?;
+    conn.execute(
+        "UPDATE time_sessions SET end_time = ?1, duration_seconds = ?2, is_running = 0, updated_at = ?3 WHERE id = ?4",
+        rusqlite::params![&at.to_rfc3339(), &duration_seconds, &now.to_rfc3339(), &session.id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn session_tag_ids(conn: &Connection, session_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT tag_id FROM session_tags WHERE session_id = ?1")
         .map_err(|e| e.to_string())?;
+    let tag_ids = stmt
+        .query_map([session_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(tag_ids)
+}
+
+/// Running sessions that were not started by, or heartbeated from, this process
+pub(crate) fn find_orphaned_sessions(
+    conn: &Connection,
+    app_started_at: DateTime<Utc>,
+) -> Result<Vec<OrphanedSession>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, last_heartbeat FROM time_sessions WHERE is_running = 1")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut orphaned = Vec::new();
+    for (session_id, heartbeat) in rows {
+        let last_heartbeat: Option<DateTime<Utc>> = heartbeat.and_then(|h| h.parse().ok());
+        if last_heartbeat.map_or(true, |h| h < app_started_at) {
+            orphaned.push(OrphanedSession {
+                session: fetch_session(conn, &session_id)?,
+                last_heartbeat,
+            });
+        }
+    }
+
+    Ok(orphaned)
+}
+
+/// End a session at `end_time`, or where its pause began if it is paused, counting
+/// only the time it was not paused
+pub(crate) fn close_session_at(
+    conn: &Connection,
+    session: &TimeSession,
+    end_time: DateTime<Utc>,
+) -> Result<(), String> {
+    let end_time = session
+        .paused_at
+        .map_or(end_time, |paused_at| paused_at.min(end_time))
+        .max(session.start_time);
+
+    conn.execute(
+        "DELETE FROM session_pauses WHERE session_id = ?1 AND resumed_at IS NULL",
+        [&session.id],
+    )
+    .map_err(|e| e.to_string())?;
+    let duration_seconds = active_seconds(conn, &session.id, session.start_time, end_time)?;
+
+    conn.execute(
+        "UPDATE time_sessions SET end_time = ?1, duration_seconds = ?2, is_running = 0, updated_at = ?3 WHERE id = ?4",
+        rusqlite::params![
+            &end_time.to_rfc3339(),
+            &duration_seconds,
+            &Utc::now().to_rfc3339(),
+            &session.id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
 
     Ok(())
 }
+
+#[tauri::command]
+pub fn get_orphaned_sessions(db: State<Mutex<Connection>>) -> Result<Vec<OrphanedSession>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    find_orphaned_sessions(&conn, crate::session_recovery::app_started_at())
+}
+
+/// Resolve a session left running by an unclean exit.
+/// `action` is "close_at_heartbeat", "close_now", "resume" or "discard".
+#[tauri::command]
+pub fn resolve_orphaned_session(
+    db: State<Mutex<Connection>>,
+    session_id: String,
+    action: String,
+) -> Result<Option<TimeSession>, String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let orphan = find_orphaned_sessions(&tx, crate::session_recovery::app_started_at())?
+        .into_iter()
+        .find(|o| o.session.id == session_id)
+        .ok_or("Session is not orphaned")?;
+    let now = Utc::now();
+
+    match action.as_str() {
+        "close_at_heartbeat" => {
+            let end_time = orphan.last_heartbeat.unwrap_or(orphan.session.start_time);
+            close_session_at(&tx, &orphan.session, end_time)?;
+        }
+        "close_now" => close_session_at(&tx, &orphan.session, now)?,
+        "resume" => {
+            tx.execute(
+                "UPDATE time_sessions SET last_heartbeat = ?1 WHERE id = ?2",
+                rusqlite::params![&now.to_rfc3339(), &session_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        "discard" => {
+            // Goes to the trash like any deleted session, so it can be undone
+            journal::perform(&tx, "delete_session", &session_id)?;
+            tx.commit().map_err(|e| e.to_string())?;
+            return Ok(None);
+        }
+        other => return Err(format!("Unknown recovery action: {}", other)),
+    }
+
+    let session = fetch_session(&tx, &session_id)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(Some(session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+
+    const HOUR: i64 = 3600;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn connection_with_project() -> Connection {
+        let conn = test_connection();
+        conn.execute(
+            "INSERT INTO projects (id, name, created_at, updated_at) VALUES ('p1', 'Project', '', '')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    /// A session of 'p1'; a running one has no end or duration
+    fn add_session(conn: &Connection, id: &str, start: &str, end: Option<&str>, duration: Option<i64>) -> TimeSession {
+        conn.execute(
+            "INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, is_running,
+             created_at, updated_at)
+             VALUES (?1, 'p1', ?2, ?3, ?4, ?5, ?2, ?2)",
+            rusqlite::params![
+                id,
+                &utc(start).to_rfc3339(),
+                &end.map(|e| utc(e).to_rfc3339()),
+                &duration,
+                end.is_none()
+            ],
+        )
+        .unwrap();
+        fetch_session(conn, id).unwrap()
+    }
+
+    fn add_pause(conn: &Connection, session_id: &str, paused_at: &str, resumed_at: Option<&str>) {
+        conn.execute(
+            "INSERT INTO session_pauses (id, session_id, paused_at, resumed_at) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                &Uuid::new_v4().to_string(),
+                session_id,
+                &utc(paused_at).to_rfc3339(),
+                &resumed_at.map(|r| utc(r).to_rfc3339())
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn split_counts_each_pause_in_its_own_part() {
+        let conn = connection_with_project();
+        // 09:00–17:00 with a 1 h pause before the split and a 2 h one after it
+        let session = add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T17:00:00Z"), Some(5 * HOUR));
+        add_pause(&conn, "s1", "2024-01-15T10:00:00Z", Some("2024-01-15T11:00:00Z"));
+        add_pause(&conn, "s1", "2024-01-15T14:00:00Z", Some("2024-01-15T16:00:00Z"));
+
+        let second_id = split_session_at(&conn, &session, utc("2024-01-15T13:00:00Z"), "p1", Utc::now()).unwrap();
+
+        let first = fetch_session(&conn, "s1").unwrap();
+        let second = fetch_session(&conn, &second_id).unwrap();
+        assert_eq!(first.end_time, Some(utc("2024-01-15T13:00:00Z")));
+        assert_eq!(first.duration_seconds, Some(3 * HOUR));
+        assert_eq!(second.duration_seconds, Some(2 * HOUR));
+        assert_eq!(second.paused_seconds, 2 * HOUR);
+    }
+
+    #[test]
+    fn split_inside_a_pause_cuts_it_in_two() {
+        let conn = connection_with_project();
+        let session = add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T13:00:00Z"), Some(2 * HOUR));
+        add_pause(&conn, "s1", "2024-01-15T10:00:00Z", Some("2024-01-15T12:00:00Z"));
+
+        let second_id = split_session_at(&conn, &session, utc("2024-01-15T11:00:00Z"), "p1", Utc::now()).unwrap();
+
+        let first = fetch_session(&conn, "s1").unwrap();
+        let second = fetch_session(&conn, &second_id).unwrap();
+        assert_eq!((first.duration_seconds, first.paused_seconds), (Some(HOUR), HOUR));
+        assert_eq!((second.duration_seconds, second.paused_seconds), (Some(HOUR), HOUR));
+    }
+
+    #[test]
+    fn split_of_a_paused_running_session_keeps_the_second_part_paused() {
+        let conn = connection_with_project();
+        let session = add_session(&conn, "s1", "2024-01-15T09:00:00Z", None, None);
+        add_pause(&conn, "s1", "2024-01-15T10:00:00Z", None);
+
+        let now = utc("2024-01-15T12:00:00Z");
+        let second_id = split_session_at(&conn, &session, utc("2024-01-15T11:00:00Z"), "p1", now).unwrap();
+
+        let first = fetch_session(&conn, "s1").unwrap();
+        let second = fetch_session(&conn, &second_id).unwrap();
+        assert!(!first.is_running);
+        assert_eq!(first.duration_seconds, Some(HOUR));
+        assert_eq!(first.paused_at, None);
+        assert!(second.is_running);
+        assert_eq!(second.duration_seconds, None);
+        assert_eq!(second.paused_at, Some(utc("2024-01-15T11:00:00Z")));
+    }
+
+    #[test]
+    fn merge_records_the_gap_between_sessions_as_a_pause() {
+        let conn = connection_with_project();
+        let first = add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T10:00:00Z"), Some(HOUR));
+        let second = add_session(&conn, "s2", "2024-01-15T12:00:00Z", Some("2024-01-15T13:00:00Z"), Some(HOUR));
+
+        merge_session_into(&conn, &first, &second).unwrap();
+
+        let merged = fetch_session(&conn, "s1").unwrap();
+        assert_eq!(merged.end_time, Some(utc("2024-01-15T13:00:00Z")));
+        assert_eq!(merged.duration_seconds, Some(2 * HOUR));
+        assert_eq!(merged.paused_seconds, 2 * HOUR);
+        let active = active_seconds(&conn, "s1", merged.start_time, merged.end_time.unwrap()).unwrap();
+        assert_eq!(active, 2 * HOUR);
+    }
+
+    #[test]
+    fn merge_of_overlapping_sessions_counts_the_overlap_once() {
+        let conn = connection_with_project();
+        let first = add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T11:00:00Z"), Some(2 * HOUR));
+        let second = add_session(&conn, "s2", "2024-01-15T10:00:00Z", Some("2024-01-15T12:00:00Z"), Some(2 * HOUR));
+
+        merge_session_into(&conn, &second, &first).unwrap();
+
+        let merged = fetch_session(&conn, "s2").unwrap();
+        assert_eq!(merged.start_time, utc("2024-01-15T09:00:00Z"));
+        assert_eq!(merged.duration_seconds, Some(3 * HOUR));
+        assert_eq!(merged.paused_seconds, 0);
+        assert!(fetch_session(&conn, "s1").is_err());
+    }
+
+    #[test]
+    fn merge_into_a_running_session_keeps_it_running_without_the_gap() {
+        let conn = connection_with_project();
+        let finished = add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T10:00:00Z"), Some(HOUR));
+        let running = add_session(&conn, "s2", "2024-01-15T11:00:00Z", None, None);
+
+        merge_session_into(&conn, &running, &finished).unwrap();
+
+        let merged = fetch_session(&conn, "s2").unwrap();
+        assert!(merged.is_running);
+        assert_eq!(merged.start_time, utc("2024-01-15T09:00:00Z"));
+        let active = active_seconds(&conn, "s2", merged.start_time, utc("2024-01-15T12:00:00Z")).unwrap();
+        assert_eq!(active, 2 * HOUR);
+    }
+
+    fn add_details(conn: &Connection, session_id: &str) {
+        conn.execute(
+            "INSERT INTO tags (id, name, created_at, updated_at) VALUES ('t1', 'Tag', '', '')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO session_tags (session_id, tag_id) VALUES (?1, 't1')", [session_id])
+            .unwrap();
+        conn.execute("UPDATE time_sessions SET notes = 'Notes', billable = 0 WHERE id = ?1", [session_id])
+            .unwrap();
+    }
+
+    #[test]
+    fn keeping_idle_time_leaves_the_session_alone() {
+        let conn = connection_with_project();
+        add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T12:00:00Z"), Some(3 * HOUR));
+
+        let now = utc("2024-01-15T18:00:00Z");
+        let affected = resolve_idle_span(
+            &conn,
+            "s1",
+            utc("2024-01-15T10:00:00Z"),
+            utc("2024-01-15T11:00:00Z"),
+            "keep",
+            None,
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(affected.len(), 1);
+        assert_eq!(affected[0].duration_seconds, Some(3 * HOUR));
+        assert_eq!(affected[0].end_time, Some(utc("2024-01-15T12:00:00Z")));
+    }
+
+    #[test]
+    fn discarding_idle_time_keeps_the_rest_of_the_session() {
+        let conn = connection_with_project();
+        add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T13:00:00Z"), Some(4 * HOUR));
+        add_details(&conn, "s1");
+        add_pause(&conn, "s1", "2024-01-15T10:30:00Z", Some("2024-01-15T10:45:00Z"));
+
+        let now = utc("2024-01-15T18:00:00Z");
+        let affected =
+            resolve_idle_span(&conn, "s1", utc("2024-01-15T10:00:00Z"), utc("2024-01-15T11:00:00Z"), "discard", None, now).unwrap();
+
+        assert_eq!(affected.len(), 2);
+        let first = fetch_session(&conn, "s1").unwrap();
+        assert_eq!(first.end_time, Some(utc("2024-01-15T10:00:00Z")));
+        assert_eq!((first.duration_seconds, first.paused_seconds), (Some(HOUR), 0));
+        let rest = &affected[1];
+        assert_eq!(rest.start_time, utc("2024-01-15T11:00:00Z"));
+        assert_eq!(rest.duration_seconds, Some(2 * HOUR));
+        assert_eq!(rest.notes.as_deref(), Some("Notes"));
+        assert!(!rest.billable);
+        assert_eq!(session_tag_ids(&conn, &rest.id).unwrap(), vec!["t1".to_string()]);
+    }
+
+    #[test]
+    fn idle_until_now_stops_a_running_session_without_an_empty_continuation() {
+        let conn = connection_with_project();
+        add_session(&conn, "s1", "2024-01-15T09:00:00Z", None, None);
+
+        // An idle end past "now" is clamped to it
+        let now = utc("2024-01-15T12:00:00Z");
+        let affected =
+            resolve_idle_span(&conn, "s1", utc("2024-01-15T11:00:00Z"), utc("2024-01-15T12:05:00Z"), "discard", None, now).unwrap();
+
+        assert_eq!(affected.len(), 1);
+        let session = fetch_session(&conn, "s1").unwrap();
+        assert!(!session.is_running);
+        assert_eq!(session.end_time, Some(utc("2024-01-15T11:00:00Z")));
+        assert_eq!(session.duration_seconds, Some(2 * HOUR));
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM time_sessions", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn idle_inside_a_running_session_leaves_the_rest_running() {
+        let conn = connection_with_project();
+        add_session(&conn, "s1", "2024-01-15T09:00:00Z", None, None);
+
+        let now = utc("2024-01-15T12:00:00Z");
+        let affected =
+            resolve_idle_span(&conn, "s1", utc("2024-01-15T10:00:00Z"), utc("2024-01-15T11:00:00Z"), "discard", None, now).unwrap();
+
+        let running: i64 =
+            conn.query_row("SELECT COUNT(*) FROM time_sessions WHERE is_running = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(running, 1);
+        assert!(affected[1].is_running);
+        assert_eq!(affected[1].start_time, utc("2024-01-15T11:00:00Z"));
+    }
+
+    #[test]
+    fn reassigning_idle_time_books_it_to_the_other_project() {
+        let conn = connection_with_project();
+        conn.execute(
+            "INSERT INTO projects (id, name, created_at, updated_at) VALUES ('p2', 'Other', '', '')",
+            [],
+        )
+        .unwrap();
+        add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T12:00:00Z"), Some(3 * HOUR));
+        add_details(&conn, "s1");
+
+        let now = utc("2024-01-15T18:00:00Z");
+        let affected =
+            resolve_idle_span(&conn, "s1", utc("2024-01-15T10:00:00Z"), utc("2024-01-15T11:00:00Z"), "reassign", Some("p2"), now)
+                .unwrap();
+
+        let reassigned = affected.last().unwrap();
+        assert_eq!(reassigned.project_id, "p2");
+        assert_eq!(reassigned.duration_seconds, Some(HOUR));
+        assert_eq!(reassigned.notes.as_deref(), Some("Notes"));
+        assert!(!reassigned.billable);
+        assert_eq!(session_tag_ids(&conn, &reassigned.id).unwrap(), vec!["t1".to_string()]);
+    }
+
+    #[test]
+    fn reassigning_idle_time_runs_the_manual_entry_checks() {
+        let conn = connection_with_project();
+        conn.execute(
+            "INSERT INTO projects (id, name, status, created_at, updated_at)
+             VALUES ('p2', 'Other', 'active', '', ''), ('p3', 'Archived', 'archived', '', '')",
+            [],
+        )
+        .unwrap();
+        add_session(&conn, "s1", "2024-01-15T09:00:00Z", Some("2024-01-15T12:00:00Z"), Some(3 * HOUR));
+        conn.execute(
+            "INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, is_running,
+             created_at, updated_at)
+             VALUES ('s2', 'p2', ?1, ?2, 1800, 0, ?1, ?1)",
+            [&utc("2024-01-15T10:15:00Z").to_rfc3339(), &utc("2024-01-15T10:45:00Z").to_rfc3339()],
+        )
+        .unwrap();
+
+        let now = utc("2024-01-15T18:00:00Z");
+        let (idle_start, idle_end) = (utc("2024-01-15T10:00:00Z"), utc("2024-01-15T11:00:00Z"));
+        for target in ["missing", "p3", "p2"] {
+            let tx = conn.unchecked_transaction().unwrap();
+            assert!(resolve_idle_span(&tx, "s1", idle_start, idle_end, "reassign", Some(target), now).is_err());
+            tx.rollback().unwrap();
+        }
+        let session = fetch_session(&conn, "s1").unwrap();
+        assert_eq!(session.end_time, Some(utc("2024-01-15T12:00:00Z")));
+    }
+}
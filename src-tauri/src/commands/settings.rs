@@ -0,0 +1,130 @@
+use chrono::{NaiveTime, Utc, Weekday};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::State;
+
+/// How many sessions may run at once: "parallel", "one_per_project" or "one_global"
+pub const SESSION_POLICY: &str = "session_policy";
+/// What to do on startup with sessions left running by a crash: "close_at_heartbeat" or "ask"
+pub const ORPHAN_RECOVERY: &str = "orphan_recovery";
+/// Issuer block and numbering printed on invoices
+pub const INVOICE_ISSUER_NAME: &str = "invoice_issuer_name";
+pub const INVOICE_ISSUER_ADDRESS: &str = "invoice_issuer_address";
+pub const INVOICE_ISSUER_TAX_ID: &str = "invoice_issuer_tax_id";
+pub const INVOICE_NUMBER_PREFIX: &str = "invoice_number_prefix";
+/// IANA zone (e.g. "America/Bogota") new sessions are recorded in and days are counted in;
+/// the system zone when unset
+pub const TIMEZONE: &str = "timezone";
+/// First day of the week for weekly rollups ("monday" … "sunday"); Monday (ISO) when unset
+pub const WEEK_START: &str = "week_start";
+/// Working hours as local "HH:MM" times (09:00–18:00 when unset) and working days as
+/// a comma-separated list such as "mon,tue,wed,thu,fri" (the default)
+pub const WORKDAY_START: &str = "workday_start";
+pub const WORKDAY_END: &str = "workday_end";
+pub const WORKDAYS: &str = "workdays";
+/// Reminders: minutes without a running session during working hours before a nudge,
+/// and hours after which a running session is flagged; 0 disables either
+pub const REMINDER_IDLE_MINUTES: &str = "reminder_idle_minutes";
+pub const REMINDER_MAX_SESSION_HOURS: &str = "reminder_max_session_hours";
+
+pub(crate) fn read_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// A "HH:MM" setting as a local time, `default` when unset or unreadable
+pub(crate) fn read_time_setting(conn: &Connection, key: &str, default: NaiveTime) -> Result<NaiveTime, String> {
+    Ok(read_setting(conn, key)?
+        .and_then(|value| NaiveTime::parse_from_str(&value, "%H:%M").ok())
+        .unwrap_or(default))
+}
+
+/// The configured working days, Monday to Friday when unset
+pub(crate) fn read_workdays(conn: &Connection) -> Result<Vec<Weekday>, String> {
+    Ok(match read_setting(conn, WORKDAYS)? {
+        Some(days) => days.split(',').filter_map(|day| day.trim().parse().ok()).collect(),
+        None => vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+    })
+}
+
+pub(crate) fn write_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        rusqlite::params![key, value, &Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn validate_setting(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        SESSION_POLICY => match value {
+            "parallel" | "one_per_project" | "one_global" => Ok(()),
+            _ => Err(format!("Invalid session policy: {}", value)),
+        },
+        ORPHAN_RECOVERY => match value {
+            "close_at_heartbeat" | "ask" => Ok(()),
+            _ => Err(format!("Invalid orphan recovery mode: {}", value)),
+        },
+        TIMEZONE => crate::timezone::parse_timezone(value).map(|_| ()),
+        WEEK_START => value
+            .parse::<chrono::Weekday>()
+            .map(|_| ())
+            .map_err(|_| format!("Invalid week start: {}", value)),
+        WORKDAY_START | WORKDAY_END => NaiveTime::parse_from_str(value, "%H:%M")
+            .map(|_| ())
+            .map_err(|_| format!("Invalid time: {}", value)),
+        WORKDAYS => value
+            .split(',')
+            .try_for_each(|day| day.trim().parse::<chrono::Weekday>().map(|_| ()))
+            .map_err(|_| format!("Invalid working days: {}", value)),
+        REMINDER_IDLE_MINUTES | REMINDER_MAX_SESSION_HOURS => value
+            .parse::<f64>()
+            .ok()
+            .filter(|n| *n >= 0.0)
+            .map(|_| ())
+            .ok_or_else(|| format!("Invalid reminder threshold: {}", value)),
+        super::backup::AUTO_BACKUP_INTERVAL_HOURS => value
+            .parse::<u32>()
+            .map(|_| ())
+            .map_err(|_| format!("Invalid backup interval: {}", value)),
+        super::backup::AUTO_BACKUP_RETENTION => match value.parse::<u32>() {
+            Ok(n) if n >= 1 => Ok(()),
+            _ => Err(format!("Invalid backup retention: {}", value)),
+        },
+        super::journal::TRASH_RETENTION_DAYS => match value.parse::<u32>() {
+            Ok(n) if n >= 1 => Ok(()),
+            _ => Err(format!("Invalid trash retention: {}", value)),
+        },
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn get_settings(db: State<Mutex<Connection>>) -> Result<HashMap<String, String>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM settings")
+        .map_err(|e| e.to_string())?;
+
+    let settings = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<String, String>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn set_setting(db: State<Mutex<Connection>>, key: String, value: String) -> Result<(), String> {
+    validate_setting(&key, &value)?;
+
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    write_setting(&conn, &key, &value)
+}
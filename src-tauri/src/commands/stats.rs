@@ -1,16 +1,48 @@
-use crate::models::{DailyStats, ProjectStats, ProjectTimeBreakdown};
+use crate::commands::{sessions, settings};
+use crate::commands::tags::{tag_filter_clause, tag_filter_param};
+use crate::models::{
+    ClientTimeBreakdown, DailyStats, PeriodStats, ProjectBillingBreakdown, ProjectStats, ProjectTimeBreakdown,
+    SessionPause, TagTimeBreakdown, TaskTimeBreakdown,
+};
+use crate::timezone;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
 use rusqlite::Connection;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Mutex;
 use tauri::State;
 
+/// Seconds elapsed so far in each running session, pauses left out, as (project_id, seconds)
+pub(crate) fn running_elapsed(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<(String, i64)>, String> {
+    sessions::query_running_sessions(conn)?
+        .into_iter()
+        .map(|session| {
+            let seconds = sessions::active_seconds(conn, &session.id, session.start_time, now.max(session.start_time))?;
+            Ok((session.project_id, seconds))
+        })
+        .collect()
+}
+
+/// Add the live time of running sessions to all-time project stats
+fn add_running_to_project_stats(stats: &mut ProjectStats, seconds: i64) {
+    stats.running_seconds += seconds;
+    stats.total_seconds += seconds;
+    stats.total_hours = hours(stats.total_seconds);
+    stats.session_count += 1;
+}
+
+/// All-time stats for a project. With `include_running`, the time elapsed so far in
+/// its running sessions is added to the totals and reported in `running_seconds`.
 #[tauri::command]
 pub fn get_project_stats(
     db: State<Mutex<Connection>>,
     project_id: String,
+    include_running: Option<bool>,
 ) -> Result<ProjectStats, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
 
-    let stats = conn
+    let mut stats = conn
         .query_row(
             "SELECT
                 project_id,
@@ -27,6 +59,7 @@ pub fn get_project_stats(
                     total_seconds: row.get(1)?,
                     total_hours: row.get(2)?,
                     session_count: row.get(3)?,
+                    running_seconds: 0,
                 })
             },
         )
@@ -35,13 +68,27 @@ pub fn get_project_stats(
             total_seconds: 0,
             total_hours: 0.0,
             session_count: 0,
+            running_seconds: 0,
         });
 
+    if include_running.unwrap_or(false) {
+        for (running_project, seconds) in running_elapsed(&conn, Utc::now())? {
+            if running_project == project_id {
+                add_running_to_project_stats(&mut stats, seconds);
+            }
+        }
+    }
+
     Ok(stats)
 }
 
+/// All-time stats per project. With `include_running`, running sessions count
+/// with the time elapsed so far.
 #[tauri::command]
-pub fn get_all_projects_stats(db: State<Mutex<Connection>>) -> Result<Vec<ProjectStats>, String> {
+pub fn get_all_projects_stats(
+    db: State<Mutex<Connection>>,
+    include_running: Option<bool>,
+) -> Result<Vec<ProjectStats>, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = conn
@@ -58,153 +105,783 @@ pub fn get_all_projects_stats(db: State<Mutex<Connection>>) -> Result<Vec<Projec
         )
         .map_err(|e| e.to_string())?;
 
-    let stats = stmt
+    let mut stats = stmt
         .query_map([], |row| {
             Ok(ProjectStats {
                 project_id: row.get(0)?,
                 total_seconds: row.get(1)?,
                 total_hours: row.get(2)?,
                 session_count: row.get(3)?,
+                running_seconds: 0,
             })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<ProjectStats>, _>>()
         .map_err(|e| e.to_string())?;
 
+    if include_running.unwrap_or(false) {
+        for (project_id, seconds) in running_elapsed(&conn, Utc::now())? {
+            match stats.iter_mut().find(|s| s.project_id == project_id) {
+                Some(existing) => add_running_to_project_stats(existing, seconds),
+                None => {
+                    let mut fresh = ProjectStats {
+                        project_id,
+                        total_seconds: 0,
+                        total_hours: 0.0,
+                        session_count: 0,
+                        running_seconds: 0,
+                    };
+                    add_running_to_project_stats(&mut fresh, seconds);
+                    stats.push(fresh);
+                }
+            }
+        }
+        stats.sort_by_key(|s| Reverse(s.total_seconds));
+    }
+
     Ok(stats)
 }
 
-#[tauri::command]
-pub fn get_daily_stats(
-    db: State<Mutex<Connection>>,
-    start_date: String,
-    end_date: String,
-) -> Result<Vec<DailyStats>, String> {
-    let conn = db.lock().map_err(|e| e.to_string())?;
+/// A session with everything the date-range aggregations group by. Running
+/// sessions (when included) end at the query time.
+struct StatsSession {
+    id: String,
+    project_id: String,
+    project_name: String,
+    client_id: Option<String>,
+    client_name: Option<String>,
+    hourly_rate: Option<f64>, // project rate, else client rate
+    currency: Option<String>,
+    task_id: Option<String>,
+    task_name: Option<String>,
+    task_status: Option<String>,
+    task_estimated_hours: Option<f64>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration_seconds: i64,
+    billable: bool,
+    running: bool,
+    tz: Tz,
+    pauses: Vec<SessionPause>,
+}
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT
-                DATE(start_time) as date,
-                COALESCE(SUM(duration_seconds), 0) as total_seconds,
-                COALESCE(SUM(duration_seconds) / 3600.0, 0) as total_hours
-             FROM time_sessions
-             WHERE is_running = 0
-             AND DATE(start_time) >= ?1
-             AND DATE(start_time) <= ?2
-             GROUP BY DATE(start_time)
-             ORDER BY date ASC"
-        )
-        .map_err(|e| e.to_string())?;
+/// The part of a session that falls on one local calendar day
+pub(crate) struct DayPortion {
+    pub date: NaiveDate,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub seconds: i64,
+}
 
-    let mut daily_stats_vec = Vec::new();
+/// Split a session at the local midnights of its time zone. The recorded duration
+/// is shared out in proportion to the time each day was not paused, so the portions
+/// add up to it; a pause still on counts until `end`.
+pub(crate) fn day_portions(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration_seconds: i64,
+    tz: Tz,
+    pauses: &[SessionPause],
+) -> Vec<DayPortion> {
+    let pieces = timezone::split_at_midnights(start, end, tz);
 
-    let rows = stmt
-        .query_map([&start_date, &end_date], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, i64>(1)?,
-                row.get::<_, f64>(2)?,
-            ))
-        })
-        .map_err(|e| e.to_string())?;
+    if pieces.len() <= 1 {
+        return vec![DayPortion {
+            date: timezone::local_date(start, tz),
+            start,
+            end,
+            seconds: duration_seconds,
+        }];
+    }
 
-    for row in rows {
-        let (date, total_seconds, total_hours) = row.map_err(|e| e.to_string())?;
+    let active = |piece_start: DateTime<Utc>, piece_end: DateTime<Utc>| -> i64 {
+        let paused: i64 = pauses
+            .iter()
+            .map(|pause| {
+                let resumed_at = pause.resumed_at.unwrap_or(end);
+                (resumed_at.min(piece_end) - pause.paused_at.max(piece_start)).num_seconds().max(0)
+            })
+            .sum();
+        ((piece_end - piece_start).num_seconds() - paused).max(0)
+    };
+    // Wall-clock time is the measure when the pauses leave nothing to go by
+    let mut weights: Vec<i64> = pieces.iter().map(|(_, s, e)| active(*s, *e)).collect();
+    if weights.iter().sum::<i64>() <= 0 {
+        weights = pieces.iter().map(|(_, s, e)| (*e - *s).num_seconds()).collect();
+    }
+    let total_weight = weights.iter().sum::<i64>().max(1);
 
-        // Get project breakdown for this date
-        let project_breakdown = get_project_breakdown_for_date(&conn, &date)?;
+    let last = pieces.len() - 1;
+    let mut assigned = 0;
+    pieces
+        .into_iter()
+        .zip(weights)
+        .enumerate()
+        .map(|(index, ((date, piece_start, piece_end), weight))| {
+            let seconds = if index == last {
+                duration_seconds - assigned
+            } else {
+                weight * duration_seconds / total_weight
+            };
+            assigned += seconds;
+            DayPortion {
+                date,
+                start: piece_start,
+                end: piece_end,
+                seconds,
+            }
+        })
+        .collect()
+}
 
-        daily_stats_vec.push(DailyStats {
-            date,
-            total_seconds,
-            total_hours,
-            project_breakdown,
-        });
-    }
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", value))
+}
 
-    Ok(daily_stats_vec)
+fn hours(seconds: i64) -> f64 {
+    seconds as f64 / 3600.0
 }
 
-fn get_project_breakdown_for_date(
+/// Sessions that may cover any day of `start_date..=end_date` in their own zone.
+/// The SQL window is padded by two days so every zone offset is caught; the exact
+/// cut happens per day in `for_each_portion`.
+fn load_stats_sessions(
     conn: &Connection,
-    date: &str,
-) -> Result<Vec<ProjectTimeBreakdown>, String> {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    project_id: Option<&str>,
+    client_id: Option<&str>,
+    tag_filter: &Option<String>,
+    include_running: bool,
+) -> Result<Vec<StatsSession>, String> {
+    let window_start = (start_date - Duration::days(2)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let window_end = (end_date + Duration::days(2)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let user_tz = timezone::user_timezone(conn);
+    let now = Utc::now();
+
     let mut stmt = conn
-        .prepare(
+        .prepare(&format!(
             "SELECT
-                ts.project_id,
-                p.name as project_name,
-                c.name as client_name,
-                COALESCE(SUM(ts.duration_seconds), 0) as total_seconds,
-                COALESCE(SUM(ts.duration_seconds) / 3600.0, 0) as total_hours
+                ts.id, ts.project_id, p.name, p.client_id, c.name,
+                COALESCE(p.hourly_rate, c.hourly_rate), COALESCE(p.currency, c.currency),
+                ts.task_id, t.name, t.status, t.estimated_hours,
+                ts.start_time, ts.end_time, COALESCE(ts.duration_seconds, 0), ts.billable, ts.timezone,
+                ts.is_running
              FROM time_sessions ts
              JOIN projects p ON ts.project_id = p.id
              LEFT JOIN clients c ON p.client_id = c.id
-             WHERE ts.is_running = 0
-             AND DATE(ts.start_time) = ?1
-             GROUP BY ts.project_id, p.name, c.name
-             ORDER BY total_seconds DESC"
-        )
+             LEFT JOIN tasks t ON ts.task_id = t.id
+             WHERE ((ts.is_running = 0 AND ts.end_time IS NOT NULL) OR (?6 = 1 AND ts.is_running = 1))
+             AND ts.start_time < ?1
+             AND (ts.is_running = 1 OR ts.end_time > ?2)
+             AND (?3 IS NULL OR ts.project_id = ?3)
+             AND (?4 IS NULL OR p.client_id = ?4)
+             AND {}
+             ORDER BY ts.start_time ASC",
+            tag_filter_clause(5)
+        ))
         .map_err(|e| e.to_string())?;
 
-    let breakdown = stmt
-        .query_map([date], |row| {
-            Ok(ProjectTimeBreakdown {
-                project_id: row.get(0)?,
-                project_name: row.get(1)?,
-                client_name: row.get(2)?,
-                total_seconds: row.get(3)?,
-                total_hours: row.get(4)?,
-            })
-        })
+    let mut sessions = stmt
+        .query_map(
+            rusqlite::params![
+                &window_end.to_rfc3339(),
+                &window_start.to_rfc3339(),
+                project_id,
+                client_id,
+                tag_filter,
+                include_running
+            ],
+            |row| {
+                let start: DateTime<Utc> = row.get::<_, String>(11)?.parse().unwrap();
+                let running = row.get::<_, i32>(16)? == 1;
+                let (end, duration_seconds) = if running {
+                    (now.max(start), (now - start).num_seconds().max(0))
+                } else {
+                    (row.get::<_, String>(12)?.parse().unwrap(), row.get(13)?)
+                };
+                Ok(StatsSession {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    project_name: row.get(2)?,
+                    client_id: row.get(3)?,
+                    client_name: row.get(4)?,
+                    hourly_rate: row.get(5)?,
+                    currency: row.get(6)?,
+                    task_id: row.get(7)?,
+                    task_name: row.get(8)?,
+                    task_status: row.get(9)?,
+                    task_estimated_hours: row.get(10)?,
+                    start,
+                    end,
+                    duration_seconds,
+                    billable: row.get::<_, i32>(14)? == 1,
+                    running,
+                    tz: timezone::session_timezone(row.get::<_, Option<String>>(15)?.as_deref(), user_tz),
+                    pauses: Vec::new(),
+                })
+            },
+        )
         .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<ProjectTimeBreakdown>, _>>()
+        .collect::<Result<Vec<StatsSession>, _>>()
         .map_err(|e| e.to_string())?;
 
-    Ok(breakdown)
+    // Running sessions only count the time they were not paused
+    for session in sessions.iter_mut().filter(|s| s.running) {
+        session.duration_seconds = sessions::active_seconds(conn, &session.id, session.start, session.end)?;
+    }
+
+    let mut pauses = sessions::pauses_by_session(conn)?;
+    for session in sessions.iter_mut() {
+        session.pauses = pauses.remove(&session.id).unwrap_or_default();
+    }
+
+    Ok(sessions)
+}
+
+/// Visit every day portion of `sessions` that falls within `start_date..=end_date`
+fn for_each_portion<'a>(
+    sessions: &'a [StatsSession],
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    mut visit: impl FnMut(&'a StatsSession, &DayPortion),
+) {
+    for session in sessions {
+        for portion in day_portions(session.start, session.end, session.duration_seconds, session.tz, &session.pauses) {
+            if portion.date >= start_date && portion.date <= end_date && portion.seconds > 0 {
+                visit(session, &portion);
+            }
+        }
+    }
+}
+
+fn add_to_project(breakdown: &mut HashMap<String, ProjectTimeBreakdown>, session: &StatsSession, seconds: i64) {
+    let entry = breakdown
+        .entry(session.project_id.clone())
+        .or_insert_with(|| ProjectTimeBreakdown {
+            project_id: session.project_id.clone(),
+            project_name: session.project_name.clone(),
+            client_name: session.client_name.clone(),
+            total_seconds: 0,
+            total_hours: 0.0,
+        });
+    entry.total_seconds += seconds;
+    entry.total_hours = hours(entry.total_seconds);
+}
+
+fn sorted_by_time(breakdown: HashMap<String, ProjectTimeBreakdown>) -> Vec<ProjectTimeBreakdown> {
+    let mut rows: Vec<ProjectTimeBreakdown> = breakdown.into_values().collect();
+    rows.sort_by_key(|row| Reverse(row.total_seconds));
+    rows
+}
+
+/// Per-day totals with a per-project breakdown. Sessions that cross midnight are
+/// split across the days they cover. `tag_ids` restricts the stats to sessions
+/// carrying any of those tags; with `include_running`, running sessions count with
+/// the time elapsed so far, which is also reported in `running_seconds`.
+#[tauri::command]
+pub fn get_daily_stats(
+    db: State<Mutex<Connection>>,
+    start_date: String,
+    end_date: String,
+    tag_ids: Option<Vec<String>>,
+    include_running: Option<bool>,
+) -> Result<Vec<DailyStats>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let (start, end) = (parse_date(&start_date)?, parse_date(&end_date)?);
+    let sessions = load_stats_sessions(
+        &conn,
+        start,
+        end,
+        None,
+        None,
+        &tag_filter_param(&tag_ids),
+        include_running.unwrap_or(false),
+    )?;
+
+    let mut days: BTreeMap<NaiveDate, (HashMap<String, ProjectTimeBreakdown>, i64)> = BTreeMap::new();
+    for_each_portion(&sessions, start, end, |session, portion| {
+        let (breakdown, running_seconds) = days.entry(portion.date).or_default();
+        add_to_project(breakdown, session, portion.seconds);
+        if session.running {
+            *running_seconds += portion.seconds;
+        }
+    });
+
+    Ok(days
+        .into_iter()
+        .map(|(date, (breakdown, running_seconds))| {
+            let total_seconds = breakdown.values().map(|p| p.total_seconds).sum();
+            DailyStats {
+                date: date.format("%Y-%m-%d").to_string(),
+                total_seconds,
+                total_hours: hours(total_seconds),
+                project_breakdown: sorted_by_time(breakdown),
+                running_seconds,
+            }
+        })
+        .collect())
 }
 
+/// Time per project for a date range. `tag_ids` restricts the stats to sessions
+/// carrying any of those tags; `include_running` adds the live time of running sessions.
 #[tauri::command]
 pub fn get_date_range_stats(
     db: State<Mutex<Connection>>,
     start_date: String,
     end_date: String,
+    tag_ids: Option<Vec<String>>,
+    include_running: Option<bool>,
 ) -> Result<Vec<ProjectTimeBreakdown>, String> {
     let conn = db.lock().map_err(|e| e.to_string())?;
+    let (start, end) = (parse_date(&start_date)?, parse_date(&end_date)?);
+    let sessions = load_stats_sessions(
+        &conn,
+        start,
+        end,
+        None,
+        None,
+        &tag_filter_param(&tag_ids),
+        include_running.unwrap_or(false),
+    )?;
+
+    let mut breakdown = HashMap::new();
+    for_each_portion(&sessions, start, end, |session, portion| {
+        add_to_project(&mut breakdown, session, portion.seconds);
+    });
+
+    Ok(sorted_by_time(breakdown))
+}
+
+/// Seconds logged on a project per local day of `start..=end`, running sessions
+/// included; days without time are left out
+pub(crate) fn project_daily_seconds(
+    conn: &Connection,
+    project_id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<BTreeMap<NaiveDate, i64>, String> {
+    let sessions = load_stats_sessions(conn, start, end, Some(project_id), None, &None, true)?;
+
+    let mut days = BTreeMap::new();
+    for_each_portion(&sessions, start, end, |_, portion| {
+        *days.entry(portion.date).or_insert(0) += portion.seconds;
+    });
+
+    Ok(days)
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    value.parse::<Weekday>().map_err(|_| format!("Invalid week start: {}", value))
+}
+
+/// First day of the week from the week start setting; Monday when unset
+pub(crate) fn configured_week_start(conn: &Connection) -> Result<Weekday, String> {
+    match settings::read_setting(conn, settings::WEEK_START)? {
+        Some(day) => parse_weekday(&day),
+        None => Ok(Weekday::Mon),
+    }
+}
+
+/// First day of the week containing `date` for weeks starting on `first_day`
+pub(crate) fn start_of_week(date: NaiveDate, first_day: Weekday) -> NaiveDate {
+    let offset = (date.weekday().num_days_from_monday() + 7 - first_day.num_days_from_monday()) % 7;
+    date - Duration::days(offset as i64)
+}
 
+/// Calendar unit rolled up by `get_period_stats`
+#[derive(Clone, Copy)]
+enum Period {
+    Week(Weekday),
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    fn parse(period: &str, week_start: Weekday) -> Result<Self, String> {
+        match period {
+            "week" => Ok(Period::Week(week_start)),
+            "month" => Ok(Period::Month),
+            "quarter" => Ok(Period::Quarter),
+            "year" => Ok(Period::Year),
+            _ => Err(format!("Invalid period: {}", period)),
+        }
+    }
+
+    /// First day of the period containing `date`
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week(first_day) => start_of_week(date, first_day),
+            Period::Month => date.with_day(1).unwrap(),
+            Period::Quarter => NaiveDate::from_ymd_opt(date.year(), (date.month() - 1) / 3 * 3 + 1, 1).unwrap(),
+            Period::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+        }
+    }
+
+    /// First day of the period after the one starting on `start`
+    fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week(_) => start + Duration::days(7),
+            Period::Month => start + Months::new(1),
+            Period::Quarter => start + Months::new(3),
+            Period::Year => start + Months::new(12),
+        }
+    }
+
+    /// First day of the period before the one starting on `start`
+    fn previous(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week(_) => start - Duration::days(7),
+            Period::Month => start - Months::new(1),
+            Period::Quarter => start - Months::new(3),
+            Period::Year => start - Months::new(12),
+        }
+    }
+
+    /// Weeks take the ISO number of the week holding their middle day, so Monday
+    /// weeks match ISO 8601 exactly
+    fn label(self, start: NaiveDate) -> String {
+        match self {
+            Period::Week(_) => (start + Duration::days(3)).format("%G-W%V").to_string(),
+            Period::Month => start.format("%Y-%m").to_string(),
+            Period::Quarter => format!("{}-Q{}", start.year(), (start.month() - 1) / 3 + 1),
+            Period::Year => start.format("%Y").to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PeriodTotals<'a> {
+    total_seconds: i64,
+    projects: HashMap<String, ProjectTimeBreakdown>,
+    clients: HashMap<Option<String>, ClientTimeBreakdown>,
+    sessions: HashSet<&'a str>,
+}
+
+/// Rollups by "week", "month", "quarter" or "year" for every period that overlaps
+/// the date range, each with per-project and per-client breakdowns and the total of
+/// the period before it. Periods are returned whole, including days outside the
+/// range. `week_start` (e.g. "sunday") overrides the week start setting; Monday
+/// otherwise. `tag_ids` and `include_running` work as in `get_daily_stats`.
+#[tauri::command]
+pub fn get_period_stats(
+    db: State<Mutex<Connection>>,
+    start_date: String,
+    end_date: String,
+    period: String,
+    week_start: Option<String>,
+    tag_ids: Option<Vec<String>>,
+    include_running: Option<bool>,
+) -> Result<Vec<PeriodStats>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let week_start = match week_start {
+        Some(day) => parse_weekday(&day)?,
+        None => configured_week_start(&conn)?,
+    };
+    let period = Period::parse(&period, week_start)?;
+
+    let (start, end) = (parse_date(&start_date)?, parse_date(&end_date)?);
+    let first = period.start_of(start);
+    let last = period.start_of(end);
+    let window_start = period.previous(first);
+    let window_end = period.next(last) - Duration::days(1);
+
+    let sessions = load_stats_sessions(
+        &conn,
+        window_start,
+        window_end,
+        None,
+        None,
+        &tag_filter_param(&tag_ids),
+        include_running.unwrap_or(false),
+    )?;
+
+    // One pass over the day portions, bucketed by the start of their period
+    let mut buckets: HashMap<NaiveDate, PeriodTotals> = HashMap::new();
+    for_each_portion(&sessions, window_start, window_end, |session, portion| {
+        let bucket = buckets.entry(period.start_of(portion.date)).or_default();
+        bucket.total_seconds += portion.seconds;
+        bucket.sessions.insert(session.id.as_str());
+        add_to_project(&mut bucket.projects, session, portion.seconds);
+
+        let client = bucket
+            .clients
+            .entry(session.client_id.clone())
+            .or_insert_with(|| ClientTimeBreakdown {
+                client_id: session.client_id.clone(),
+                client_name: session.client_name.clone(),
+                total_seconds: 0,
+                total_hours: 0.0,
+            });
+        client.total_seconds += portion.seconds;
+        client.total_hours = hours(client.total_seconds);
+    });
+
+    let mut rows = Vec::new();
+    let mut previous_total_seconds = buckets.get(&window_start).map_or(0, |b| b.total_seconds);
+    let mut period_start = first;
+    while period_start <= last {
+        let next_start = period.next(period_start);
+        let totals = buckets.remove(&period_start).unwrap_or_default();
+
+        let mut client_breakdown: Vec<ClientTimeBreakdown> = totals.clients.into_values().collect();
+        client_breakdown.sort_by_key(|row| Reverse(row.total_seconds));
+
+        rows.push(PeriodStats {
+            period: period.label(period_start),
+            start_date: period_start.format("%Y-%m-%d").to_string(),
+            end_date: (next_start - Duration::days(1)).format("%Y-%m-%d").to_string(),
+            total_seconds: totals.total_seconds,
+            total_hours: hours(totals.total_seconds),
+            session_count: totals.sessions.len() as i32,
+            project_breakdown: sorted_by_time(totals.projects),
+            client_breakdown,
+            previous_total_seconds,
+            previous_total_hours: hours(previous_total_seconds),
+            change_percent: (previous_total_seconds > 0).then(|| {
+                ((totals.total_seconds - previous_total_seconds) as f64 / previous_total_seconds as f64 * 1000.0)
+                    .round()
+                    / 10.0
+            }),
+        });
+
+        previous_total_seconds = totals.total_seconds;
+        period_start = next_start;
+    }
+
+    Ok(rows)
+}
+
+/// Time per tag for a date range, optionally for one project
+#[tauri::command]
+pub fn get_tag_stats(
+    db: State<Mutex<Connection>>,
+    start_date: String,
+    end_date: String,
+    project_id: Option<String>,
+) -> Result<Vec<TagTimeBreakdown>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let (start, end) = (parse_date(&start_date)?, parse_date(&end_date)?);
+    let sessions = load_stats_sessions(&conn, start, end, project_id.as_deref(), None, &None, false)?;
+
+    let mut session_tags: HashMap<String, Vec<(String, String, Option<String>)>> = HashMap::new();
     let mut stmt = conn
         .prepare(
-            "SELECT
-                ts.project_id,
-                p.name as project_name,
-                c.name as client_name,
-                COALESCE(SUM(ts.duration_seconds), 0) as total_seconds,
-                COALESCE(SUM(ts.duration_seconds) / 3600.0, 0) as total_hours
-             FROM time_sessions ts
-             JOIN projects p ON ts.project_id = p.id
-             LEFT JOIN clients c ON p.client_id = c.id
-             WHERE ts.is_running = 0
-             AND DATE(ts.start_time) >= ?1
-             AND DATE(ts.start_time) <= ?2
-             GROUP BY ts.project_id, p.name, c.name
-             ORDER BY total_seconds DESC"
+            "SELECT st.session_id, t.id, t.name, t.color
+             FROM session_tags st JOIN tags t ON st.tag_id = t.id",
         )
         .map_err(|e| e.to_string())?;
+    let links = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?, row.get(3)?))))
+        .map_err(|e| e.to_string())?;
+    for link in links {
+        let (session_id, tag) = link.map_err(|e| e.to_string())?;
+        session_tags.entry(session_id).or_default().push(tag);
+    }
 
-    let breakdown = stmt
-        .query_map([&start_date, &end_date], |row| {
-            Ok(ProjectTimeBreakdown {
-                project_id: row.get(0)?,
-                project_name: row.get(1)?,
-                client_name: row.get(2)?,
-                total_seconds: row.get(3)?,
-                total_hours: row.get(4)?,
-            })
+    let mut breakdown: HashMap<String, TagTimeBreakdown> = HashMap::new();
+    let mut counted: HashSet<(String, &str)> = HashSet::new();
+    for_each_portion(&sessions, start, end, |session, portion| {
+        for (tag_id, tag_name, color) in session_tags.get(&session.id).into_iter().flatten() {
+            let entry = breakdown.entry(tag_id.clone()).or_insert_with(|| TagTimeBreakdown {
+                tag_id: tag_id.clone(),
+                tag_name: tag_name.clone(),
+                color: color.clone(),
+                total_seconds: 0,
+                total_hours: 0.0,
+                session_count: 0,
+            });
+            entry.total_seconds += portion.seconds;
+            entry.total_hours = hours(entry.total_seconds);
+            if counted.insert((tag_id.clone(), session.id.as_str())) {
+                entry.session_count += 1;
+            }
+        }
+    });
+
+    let mut rows: Vec<TagTimeBreakdown> = breakdown.into_values().collect();
+    rows.sort_by_key(|row| Reverse(row.total_seconds));
+    Ok(rows)
+}
+
+/// Time per task for a date range, optionally for one project. Sessions without a
+/// task are grouped in a row with `task_id = None`; tasks with no time are left out.
+#[tauri::command]
+pub fn get_task_breakdown(
+    db: State<Mutex<Connection>>,
+    start_date: String,
+    end_date: String,
+    project_id: Option<String>,
+) -> Result<Vec<TaskTimeBreakdown>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    query_task_breakdown(&conn, &start_date, &end_date, project_id.as_deref())
+}
+
+pub(crate) fn query_task_breakdown(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    project_id: Option<&str>,
+) -> Result<Vec<TaskTimeBreakdown>, String> {
+    let (start, end) = (parse_date(start_date)?, parse_date(end_date)?);
+    let sessions = load_stats_sessions(conn, start, end, project_id, None, &None, false)?;
+
+    let mut breakdown: HashMap<(String, Option<String>), TaskTimeBreakdown> = HashMap::new();
+    let mut counted: HashSet<&str> = HashSet::new();
+    for_each_portion(&sessions, start, end, |session, portion| {
+        let entry = breakdown
+            .entry((session.project_id.clone(), session.task_id.clone()))
+            .or_insert_with(|| TaskTimeBreakdown {
+                project_id: session.project_id.clone(),
+                project_name: session.project_name.clone(),
+                task_id: session.task_id.clone(),
+                task_name: session.task_name.clone(),
+                status: session.task_status.clone(),
+                estimated_hours: session.task_estimated_hours,
+                total_seconds: 0,
+                total_hours: 0.0,
+                session_count: 0,
+            });
+        entry.total_seconds += portion.seconds;
+        entry.total_hours = hours(entry.total_seconds);
+        if counted.insert(session.id.as_str()) {
+            entry.session_count += 1;
+        }
+    });
+
+    let mut rows: Vec<TaskTimeBreakdown> = breakdown.into_values().collect();
+    rows.sort_by(|a, b| {
+        a.project_name
+            .cmp(&b.project_name)
+            .then(a.task_id.is_none().cmp(&b.task_id.is_none()))
+            .then(b.total_seconds.cmp(&a.total_seconds))
+    });
+    Ok(rows)
+}
+
+/// Billable hours and amounts per project for a date range, optionally for one client.
+/// The project's rate and currency win over the client's when set.
+#[tauri::command]
+pub fn get_billing_stats(
+    db: State<Mutex<Connection>>,
+    start_date: String,
+    end_date: String,
+    client_id: Option<String>,
+) -> Result<Vec<ProjectBillingBreakdown>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let (start, end) = (parse_date(&start_date)?, parse_date(&end_date)?);
+    let sessions = load_stats_sessions(&conn, start, end, None, client_id.as_deref(), &None, false)?;
+
+    let mut breakdown: HashMap<String, ProjectBillingBreakdown> = HashMap::new();
+    for_each_portion(&sessions, start, end, |session, portion| {
+        let entry = breakdown
+            .entry(session.project_id.clone())
+            .or_insert_with(|| ProjectBillingBreakdown {
+                time: ProjectTimeBreakdown {
+                    project_id: session.project_id.clone(),
+                    project_name: session.project_name.clone(),
+                    client_name: session.client_name.clone(),
+                    total_seconds: 0,
+                    total_hours: 0.0,
+                },
+                client_id: session.client_id.clone(),
+                billable_seconds: 0,
+                billable_hours: 0.0,
+                hourly_rate: session.hourly_rate,
+                currency: session.currency.clone(),
+                amount: None,
+            });
+        entry.time.total_seconds += portion.seconds;
+        if session.billable {
+            entry.billable_seconds += portion.seconds;
+        }
+    });
+
+    let mut rows: Vec<ProjectBillingBreakdown> = breakdown
+        .into_values()
+        .map(|mut row| {
+            row.time.total_hours = hours(row.time.total_seconds);
+            row.billable_hours = hours(row.billable_seconds);
+            row.amount = row
+                .hourly_rate
+                .map(|rate| (row.billable_hours * rate * 100.0).round() / 100.0);
+            row
         })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<ProjectTimeBreakdown>, _>>()
-        .map_err(|e| e.to_string())?;
+        .collect();
+    rows.sort_by_key(|row| Reverse(row.time.total_seconds));
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3600;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn pause(paused_at: &str, resumed_at: Option<&str>) -> SessionPause {
+        SessionPause {
+            id: String::new(),
+            session_id: "s1".to_string(),
+            paused_at: utc(paused_at),
+            resumed_at: resumed_at.map(utc),
+            reason: "manual".to_string(),
+        }
+    }
 
-    Ok(breakdown)
+    fn seconds_by_day(portions: &[DayPortion]) -> Vec<(String, i64)> {
+        portions
+            .iter()
+            .map(|portion| (portion.date.format("%Y-%m-%d").to_string(), portion.seconds))
+            .collect()
+    }
+
+    #[test]
+    fn session_within_a_day_keeps_its_duration() {
+        let portions = day_portions(utc("2024-01-15T09:00:00Z"), utc("2024-01-15T17:00:00Z"), 7 * HOUR, Tz::UTC, &[]);
+        assert_eq!(seconds_by_day(&portions), vec![("2024-01-15".to_string(), 7 * HOUR)]);
+    }
+
+    #[test]
+    fn unpaused_session_splits_by_wall_clock() {
+        let portions = day_portions(utc("2024-01-15T22:00:00Z"), utc("2024-01-16T02:00:00Z"), 4 * HOUR, Tz::UTC, &[]);
+        assert_eq!(
+            seconds_by_day(&portions),
+            vec![("2024-01-15".to_string(), 2 * HOUR), ("2024-01-16".to_string(), 2 * HOUR)]
+        );
+    }
+
+    #[test]
+    fn paused_time_stays_off_its_day() {
+        // Paused for the first hour before midnight and the last one after it
+        let pauses = [
+            pause("2024-01-15T22:00:00Z", Some("2024-01-15T23:00:00Z")),
+            pause("2024-01-16T03:00:00Z", None),
+        ];
+        let portions = day_portions(utc("2024-01-15T22:00:00Z"), utc("2024-01-16T04:00:00Z"), 4 * HOUR, Tz::UTC, &pauses);
+        assert_eq!(
+            seconds_by_day(&portions),
+            vec![("2024-01-15".to_string(), HOUR), ("2024-01-16".to_string(), 3 * HOUR)]
+        );
+    }
+
+    #[test]
+    fn portions_add_up_to_an_edited_duration() {
+        let pauses = [pause("2024-01-15T22:00:00Z", Some("2024-01-15T23:00:00Z"))];
+        let portions = day_portions(utc("2024-01-15T22:00:00Z"), utc("2024-01-16T02:00:00Z"), 100, Tz::UTC, &pauses);
+        assert_eq!(portions.iter().map(|p| p.seconds).sum::<i64>(), 100);
+        assert_eq!(portions[0].seconds, 33);
+    }
+
+    #[test]
+    fn session_paused_throughout_falls_back_to_wall_clock() {
+        let pauses = [pause("2024-01-15T22:00:00Z", None)];
+        let portions = day_portions(utc("2024-01-15T22:00:00Z"), utc("2024-01-16T02:00:00Z"), 60, Tz::UTC, &pauses);
+        assert_eq!(seconds_by_day(&portions), vec![("2024-01-15".to_string(), 30), ("2024-01-16".to_string(), 30)]);
+    }
 }
@@ -0,0 +1,220 @@
+use crate::models::Tag;
+use chrono::Utc;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::State;
+use uuid::Uuid;
+
+const TAG_COLUMNS: &str = "id, name, color, created_at, updated_at";
+
+fn tag_from_row(row: &rusqlite::Row) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        created_at: row.get::<_, String>(3)?.parse().unwrap(),
+        updated_at: row.get::<_, String>(4)?.parse().unwrap(),
+    })
+}
+
+/// SQL condition keeping sessions (aliased `ts`) that carry any of the tags passed
+/// as a JSON array in parameter `?{param}`; a NULL parameter keeps every session.
+pub(crate) fn tag_filter_clause(param: usize) -> String {
+    format!(
+        "(?{0} IS NULL OR ts.id IN (SELECT session_id FROM session_tags WHERE tag_id IN (SELECT value FROM json_each(?{0}))))",
+        param
+    )
+}
+
+/// Bind value for `tag_filter_clause`; no tags (or an empty list) means no filter
+pub(crate) fn tag_filter_param(tag_ids: &Option<Vec<String>>) -> Option<String> {
+    tag_ids
+        .as_ref()
+        .filter(|ids| !ids.is_empty())
+        .map(|ids| serde_json::Value::from(ids.clone()).to_string())
+}
+
+pub(crate) fn query_all_tags(conn: &Connection) -> Result<Vec<Tag>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM tags ORDER BY name COLLATE NOCASE ASC", TAG_COLUMNS))
+        .map_err(|e| e.to_string())?;
+
+    let tags = stmt
+        .query_map([], tag_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<Tag>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(tags)
+}
+
+/// All (session_id, tag_id) links
+pub(crate) fn query_all_session_tags(conn: &Connection) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT session_id, tag_id FROM session_tags")
+        .map_err(|e| e.to_string())?;
+
+    let links = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(links)
+}
+
+#[tauri::command]
+pub fn get_all_tags(db: State<Mutex<Connection>>) -> Result<Vec<Tag>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    query_all_tags(&conn)
+}
+
+#[tauri::command]
+pub fn create_tag(
+    db: State<Mutex<Connection>>,
+    name: String,
+    color: Option<String>,
+) -> Result<Tag, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let id = Uuid::new_v4().to_string();
+
+    conn.execute(
+        "INSERT INTO tags (id, name, color, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![&id, &name, &color, &now.to_rfc3339(), &now.to_rfc3339()],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => {
+            format!("A tag named \"{}\" already exists", name)
+        }
+        other => other.to_string(),
+    })?;
+
+    Ok(Tag {
+        id,
+        name,
+        color,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+#[tauri::command]
+pub fn update_tag(
+    db: State<Mutex<Connection>>,
+    id: String,
+    name: String,
+    color: Option<String>,
+) -> Result<Tag, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE tags SET name = ?1, color = ?2, updated_at = ?3 WHERE id = ?4",
+        rusqlite::params![&name, &color, &Utc::now().to_rfc3339(), &id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        &format!("SELECT {} FROM tags WHERE id = ?1", TAG_COLUMNS),
+        [&id],
+        tag_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Delete a tag and remove it from every session
+#[tauri::command]
+pub fn delete_tag(db: State<Mutex<Connection>>, id: String) -> Result<(), String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute("DELETE FROM session_tags WHERE tag_id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM tags WHERE id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_session_tags(
+    db: State<Mutex<Connection>>,
+    session_id: String,
+) -> Result<Vec<Tag>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.name, t.color, t.created_at, t.updated_at
+             FROM tags t JOIN session_tags st ON st.tag_id = t.id
+             WHERE st.session_id = ?1
+             ORDER BY t.name COLLATE NOCASE ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let tags = stmt
+        .query_map([&session_id], tag_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<Tag>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(tags)
+}
+
+/// Tag ids of every tagged session, keyed by session id, so lists can show tags
+/// without one call per session
+#[tauri::command]
+pub fn get_session_tag_map(db: State<Mutex<Connection>>) -> Result<HashMap<String, Vec<String>>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for (session_id, tag_id) in query_all_session_tags(&conn)? {
+        map.entry(session_id).or_default().push(tag_id);
+    }
+
+    Ok(map)
+}
+
+/// Replace the tags of a session
+#[tauri::command]
+pub fn set_session_tags(
+    db: State<Mutex<Connection>>,
+    session_id: String,
+    tag_ids: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut tag_ids = tag_ids;
+    tag_ids.sort();
+    tag_ids.dedup();
+
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute("DELETE FROM session_tags WHERE session_id = ?1", [&session_id])
+        .map_err(|e| e.to_string())?;
+
+    for tag_id in &tag_ids {
+        let inserted = tx
+            .execute(
+                "INSERT INTO session_tags (session_id, tag_id) SELECT ?1, id FROM tags WHERE id = ?2",
+                [&session_id, tag_id],
+            )
+            .map_err(|e| e.to_string())?;
+        if inserted == 0 {
+            return Err(format!("Tag not found: {}", tag_id));
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(tag_ids)
+}
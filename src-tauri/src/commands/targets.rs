@@ -0,0 +1,277 @@
+use crate::commands::{projects, settings, stats};
+use crate::models::{Project, TargetProgress};
+use crate::timezone;
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+
+/// How far back streaks are counted
+const STREAK_LOOKBACK_DAYS: i64 = 365;
+/// Seconds between background target checks
+const CHECK_INTERVAL_SECS: u64 = 300;
+/// Sent target alerts are kept this long so each fires once per day
+const ALERT_RETENTION_DAYS: i64 = 7;
+
+fn has_target(project: &Project) -> bool {
+    project.hours_per_day.is_some_and(|h| h > 0.0) || project.hours_per_week.is_some_and(|h| h > 0.0)
+}
+
+fn target_seconds(hours: f64) -> i64 {
+    (hours * 3600.0).round() as i64
+}
+
+fn percent(done: f64, target: f64) -> f64 {
+    (done / target * 1000.0).round() / 10.0
+}
+
+/// Consecutive days meeting the target, counting today only once it is met.
+/// Days off (outside `workdays`) with nothing logged are skipped rather than breaking the streak.
+fn daily_streak(
+    daily: &BTreeMap<NaiveDate, i64>,
+    target: i64,
+    today: NaiveDate,
+    oldest: NaiveDate,
+    workdays: &[Weekday],
+) -> i32 {
+    let logged = |day: NaiveDate| daily.get(&day).copied().unwrap_or(0);
+    let mut day = if logged(today) >= target { today } else { today - Duration::days(1) };
+    let mut streak = 0;
+
+    while day >= oldest {
+        if logged(day) >= target {
+            streak += 1;
+        } else if workdays.contains(&day.weekday()) || logged(day) > 0 {
+            break;
+        }
+        day -= Duration::days(1);
+    }
+
+    streak
+}
+
+/// Consecutive weeks meeting the target, counting the current week only once it is met
+fn weekly_streak(daily: &BTreeMap<NaiveDate, i64>, target: i64, week_start: NaiveDate, oldest: NaiveDate) -> i32 {
+    let logged = |start: NaiveDate| -> i64 {
+        daily
+            .range(start..start + Duration::days(7))
+            .map(|(_, seconds)| seconds)
+            .sum()
+    };
+    let mut start = if logged(week_start) >= target { week_start } else { week_start - Duration::days(7) };
+    let mut streak = 0;
+
+    while start >= oldest && logged(start) >= target {
+        streak += 1;
+        start -= Duration::days(7);
+    }
+
+    streak
+}
+
+fn target_progress(
+    conn: &Connection,
+    project: &Project,
+    today: NaiveDate,
+    week_start: Weekday,
+    workdays: &[Weekday],
+) -> Result<TargetProgress, String> {
+    let oldest = today - Duration::days(STREAK_LOOKBACK_DAYS);
+    let daily = stats::project_daily_seconds(conn, &project.id, oldest, today)?;
+
+    let today_seconds = daily.get(&today).copied().unwrap_or(0);
+    let today_hours = today_seconds as f64 / 3600.0;
+    let daily_target = project.hours_per_day.filter(|h| *h > 0.0);
+
+    let week_start_date = stats::start_of_week(today, week_start);
+    let week_seconds: i64 = daily.range(week_start_date..).map(|(_, seconds)| seconds).sum();
+    let week_hours = week_seconds as f64 / 3600.0;
+    let weekly_target = project.hours_per_week.filter(|h| *h > 0.0);
+
+    Ok(TargetProgress {
+        project_id: project.id.clone(),
+        project_name: project.name.clone(),
+        daily_target_hours: daily_target,
+        today_hours,
+        today_remaining_hours: daily_target.map(|target| (target - today_hours).max(0.0)),
+        today_percent: daily_target.map(|target| percent(today_hours, target)),
+        daily_streak: daily_target
+            .map_or(0, |target| daily_streak(&daily, target_seconds(target), today, oldest, workdays)),
+        weekly_target_hours: weekly_target,
+        week_start_date: week_start_date.format("%Y-%m-%d").to_string(),
+        week_hours,
+        week_remaining_hours: weekly_target.map(|target| (target - week_hours).max(0.0)),
+        week_percent: weekly_target.map(|target| percent(week_hours, target)),
+        weekly_streak: weekly_target
+            .map_or(0, |target| weekly_streak(&daily, target_seconds(target), week_start_date, oldest)),
+    })
+}
+
+/// Progress towards the daily and weekly targets of every active project that has one
+#[tauri::command]
+pub fn get_target_progress(db: State<Mutex<Connection>>) -> Result<Vec<TargetProgress>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let today = timezone::local_date(Utc::now(), timezone::user_timezone(&conn));
+    let week_start = stats::configured_week_start(&conn)?;
+    let workdays = settings::read_workdays(&conn)?;
+
+    projects::query_all_projects(&conn)?
+        .iter()
+        .filter(|p| p.status == "active" && has_target(p))
+        .map(|p| target_progress(&conn, p, today, week_start, &workdays))
+        .collect()
+}
+
+/// Record a target alert; false when it was already sent
+fn mark_alert(conn: &Connection, project_id: &str, date: &str, kind: &str) -> Result<bool, String> {
+    let inserted = conn
+        .execute(
+            "INSERT OR IGNORE INTO target_alerts (project_id, date, kind, notified_at) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![project_id, date, kind, &Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(inserted == 1)
+}
+
+/// Notify once a day per project when its daily target is reached, or when what is
+/// left exceeds the time remaining until the end of the working day. Projects with
+/// nothing logged today are left alone.
+pub(crate) fn check_daily_targets<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let db = app.state::<Mutex<Connection>>();
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let tz = timezone::user_timezone(&conn);
+    let now = Utc::now().with_timezone(&tz);
+    let today = now.date_naive();
+    let date = today.format("%Y-%m-%d").to_string();
+    let week_start = stats::configured_week_start(&conn)?;
+    let workdays = settings::read_workdays(&conn)?;
+    // On a day off there is no working day left to fall short in
+    let is_workday = workdays.contains(&today.weekday());
+
+    let workday_end =
+        settings::read_time_setting(&conn, settings::WORKDAY_END, NaiveTime::from_hms_opt(18, 0, 0).unwrap())?;
+    let hours_left = (today.and_time(workday_end) - now.naive_local()).num_seconds().max(0) as f64 / 3600.0;
+
+    conn.execute(
+        "DELETE FROM target_alerts WHERE date < ?1",
+        [&(today - Duration::days(ALERT_RETENTION_DAYS)).format("%Y-%m-%d").to_string()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for project in projects::query_all_projects(&conn)?
+        .iter()
+        .filter(|p| p.status == "active" && p.hours_per_day.is_some_and(|h| h > 0.0))
+    {
+        let progress = target_progress(&conn, project, today, week_start, &workdays)?;
+        let (Some(target), Some(remaining)) = (progress.daily_target_hours, progress.today_remaining_hours) else {
+            continue;
+        };
+        if progress.today_hours <= 0.0 {
+            continue;
+        }
+
+        let (title, body) = if remaining <= 0.0 {
+            if !mark_alert(&conn, &project.id, &date, "reached")? {
+                continue;
+            }
+            (
+                "Meta diaria cumplida".to_string(),
+                format!("{}: {:.1} h de {:.1} h hoy", project.name, progress.today_hours, target),
+            )
+        } else if is_workday && remaining > hours_left {
+            if !mark_alert(&conn, &project.id, &date, "at_risk")? {
+                continue;
+            }
+            (
+                "Meta diaria en riesgo".to_string(),
+                format!(
+                    "{}: faltan {:.1} h y quedan {:.1} h de jornada",
+                    project.name, remaining, hours_left
+                ),
+            )
+        } else {
+            continue;
+        };
+
+        if let Err(e) = app.notification().builder().title(title).body(body).show() {
+            eprintln!("Failed to show target notification: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check daily targets at startup and then every few minutes
+pub fn setup_target_alerts<R: Runtime>(app: &tauri::App<R>) {
+    let app_handle = app.handle().clone();
+
+    std::thread::spawn(move || loop {
+        if let Err(e) = check_daily_targets(&app_handle) {
+            eprintln!("Target check failed: {}", e);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3600;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn logged(days: &[(NaiveDate, i64)]) -> BTreeMap<NaiveDate, i64> {
+        days.iter().copied().collect()
+    }
+
+    #[test]
+    fn idle_days_off_do_not_break_the_streak() {
+        // Thursday 2024-01-11 to Wednesday 2024-01-17, with nothing on the weekend
+        let daily = logged(&[
+            (date(2024, 1, 11), 4 * HOUR),
+            (date(2024, 1, 12), 4 * HOUR),
+            (date(2024, 1, 15), 4 * HOUR),
+            (date(2024, 1, 16), 4 * HOUR),
+            (date(2024, 1, 17), 4 * HOUR),
+        ]);
+        let weekdays = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+
+        assert_eq!(daily_streak(&daily, 4 * HOUR, date(2024, 1, 17), date(2024, 1, 1), &weekdays), 5);
+    }
+
+    #[test]
+    fn streak_follows_the_configured_working_days() {
+        // Someone working Sunday to Thursday: Friday and Saturday off, Sunday a working day
+        let daily = logged(&[
+            (date(2024, 1, 11), 4 * HOUR),
+            (date(2024, 1, 14), 4 * HOUR),
+            (date(2024, 1, 15), 4 * HOUR),
+        ]);
+        let sunday_to_thursday = [Weekday::Sun, Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu];
+        assert_eq!(daily_streak(&daily, 4 * HOUR, date(2024, 1, 15), date(2024, 1, 1), &sunday_to_thursday), 3);
+
+        // Under the default working days the idle Friday breaks it
+        let weekdays = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+        assert_eq!(daily_streak(&daily, 4 * HOUR, date(2024, 1, 15), date(2024, 1, 1), &weekdays), 2);
+    }
+
+    #[test]
+    fn short_day_off_breaks_and_today_counts_once_met() {
+        let weekdays = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+        let daily = logged(&[
+            (date(2024, 1, 12), 4 * HOUR),
+            (date(2024, 1, 13), HOUR),
+            (date(2024, 1, 15), 4 * HOUR),
+            (date(2024, 1, 16), 2 * HOUR),
+        ]);
+
+        // Tuesday is not met yet, so the streak is Monday alone: Saturday fell short
+        assert_eq!(daily_streak(&daily, 4 * HOUR, date(2024, 1, 16), date(2024, 1, 1), &weekdays), 1);
+    }
+}
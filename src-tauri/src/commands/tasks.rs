@@ -0,0 +1,161 @@
+use crate::models::Task;
+use chrono::Utc;
+use rusqlite::Connection;
+use std::sync::Mutex;
+use tauri::State;
+use uuid::Uuid;
+
+const TASK_COLUMNS: &str =
+    "id, project_id, name, description, status, estimated_hours, created_at, updated_at";
+
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    Ok(Task {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        description: row.get(3)?,
+        status: row.get(4)?,
+        estimated_hours: row.get(5)?,
+        created_at: row.get::<_, String>(6)?.parse().unwrap(),
+        updated_at: row.get::<_, String>(7)?.parse().unwrap(),
+    })
+}
+
+fn validate_status(status: &str) -> Result<(), String> {
+    match status {
+        "todo" | "in_progress" | "done" => Ok(()),
+        _ => Err(format!("Invalid task status: {}", status)),
+    }
+}
+
+pub(crate) fn query_all_tasks(conn: &Connection) -> Result<Vec<Task>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM tasks ORDER BY project_id, created_at ASC", TASK_COLUMNS))
+        .map_err(|e| e.to_string())?;
+
+    let tasks = stmt
+        .query_map([], task_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<Task>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(tasks)
+}
+
+fn fetch_task(conn: &Connection, id: &str) -> Result<Task, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS),
+        [id],
+        task_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_project_tasks(
+    db: State<Mutex<Connection>>,
+    project_id: String,
+) -> Result<Vec<Task>, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM tasks WHERE project_id = ?1
+             ORDER BY CASE status WHEN 'in_progress' THEN 0 WHEN 'todo' THEN 1 ELSE 2 END, created_at ASC",
+            TASK_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let tasks = stmt
+        .query_map([&project_id], task_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<Task>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub fn create_task(
+    db: State<Mutex<Connection>>,
+    project_id: String,
+    name: String,
+    description: Option<String>,
+    estimated_hours: Option<f64>,
+) -> Result<Task, String> {
+    let conn = db.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let id = Uuid::new_v4().to_string();
+
+    conn.execute(
+        "INSERT INTO tasks (id, project_id, name, description, status, estimated_hours, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, 'todo', ?5, ?6, ?7)",
+        rusqlite::params![
+            &id,
+            &project_id,
+            &name,
+            &description,
+            &estimated_hours,
+            &now.to_rfc3339(),
+            &now.to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(Task {
+        id,
+        project_id,
+        name,
+        description,
+        status: "todo".to_string(),
+        estimated_hours,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+#[tauri::command]
+pub fn update_task(
+    db: State<Mutex<Connection>>,
+    id: String,
+    name: String,
+    description: Option<String>,
+    status: String,
+    estimated_hours: Option<f64>,
+) -> Result<Task, String> {
+    validate_status(&status)?;
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE tasks SET name = ?1, description = ?2, status = ?3, estimated_hours = ?4, updated_at = ?5
+         WHERE id = ?6",
+        rusqlite::params![
+            &name,
+            &description,
+            &status,
+            &estimated_hours,
+            &Utc::now().to_rfc3339(),
+            &id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    fetch_task(&conn, &id)
+}
+
+/// Delete a task; its sessions stay on the project without a task
+#[tauri::command]
+pub fn delete_task(db: State<Mutex<Connection>>, id: String) -> Result<(), String> {
+    let mut conn = db.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE time_sessions SET task_id = NULL, updated_at = ?1 WHERE task_id = ?2",
+        rusqlite::params![&Utc::now().to_rfc3339(), &id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM tasks WHERE id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
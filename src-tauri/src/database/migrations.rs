@@ -0,0 +1,513 @@
+use chrono::Utc;
+use rusqlite::{Connection, Transaction};
+use std::path::Path;
+
+type Migration = fn(&Transaction) -> rusqlite::Result<()>;
+
+/// Ordered schema migrations. Entry `i` upgrades the database to version `i + 1`
+/// (tracked in `PRAGMA user_version`). Never edit or reorder a step that has
+/// shipped; append a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    v1_initial_schema,
+    v2_settings,
+    v3_session_heartbeat,
+    v4_billing,
+    v5_invoices,
+    v6_tasks,
+    v7_tags,
+    v8_session_timezone,
+    v9_budget_alerts,
+    v10_target_alerts,
+    v11_session_pomodoros,
+    v12_session_pauses,
+    v13_operation_journal,
+];
+
+pub const LATEST_VERSION: i32 = MIGRATIONS.len() as i32;
+
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<i32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring the database up to `LATEST_VERSION`, one transaction per step.
+/// A copy of the database is written to `backup_dir` before anything is touched.
+pub fn run_migrations(conn: &mut Connection, backup_dir: &Path) -> Result<(), String> {
+    apply_migrations(conn, backup_dir, MIGRATIONS)
+}
+
+fn apply_migrations(conn: &mut Connection, backup_dir: &Path, migrations: &[Migration]) -> Result<(), String> {
+    let version = schema_version(conn).map_err(|e| e.to_string())?;
+    let latest_version = migrations.len() as i32;
+
+    if version > latest_version {
+        return Err(format!(
+            "Database schema version {} is newer than this app supports ({})",
+            version, latest_version
+        ));
+    }
+
+    if version == latest_version {
+        return Ok(());
+    }
+
+    if has_user_tables(conn).map_err(|e| e.to_string())? {
+        backup_before_migration(conn, backup_dir, version)?;
+    }
+
+    for (index, migration) in migrations.iter().enumerate().skip(version as usize) {
+        let target_version = index as i32 + 1;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+        migration(&tx).map_err(|e| format!("Migration to v{} failed: {}", target_version, e))?;
+        tx.pragma_update(None, "user_version", target_version)
+            .map_err(|e| e.to_string())?;
+
+        tx.commit().map_err(|e| e.to_string())?;
+        log::info!("Database migrated to schema v{}", target_version);
+    }
+
+    Ok(())
+}
+
+fn has_user_tables(conn: &Connection) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+fn backup_before_migration(conn: &Connection, backup_dir: &Path, version: i32) -> Result<(), String> {
+    std::fs::create_dir_all(backup_dir).map_err(|e| e.to_string())?;
+
+    let file_name = format!(
+        "timer_count-v{}-{}.db",
+        version,
+        Utc::now().format("%Y%m%d%H%M%S")
+    );
+    let backup_path = backup_dir.join(file_name);
+
+    // VACUUM INTO writes a consistent snapshot even if the WAL has pending pages
+    conn.execute("VACUUM INTO ?1", [backup_path.to_string_lossy()])
+        .map_err(|e| format!("Failed to back up database before migrating: {}", e))?;
+
+    log::info!("Pre-migration backup written to {}", backup_path.display());
+    Ok(())
+}
+
+/// v1: the original schema. Uses IF NOT EXISTS so installs that predate
+/// versioning (user_version = 0 but tables present) pass through untouched.
+fn v1_initial_schema(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS clients (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            color TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            client_id TEXT,
+            color TEXT,
+            priority INTEGER NOT NULL DEFAULT 2,
+            status TEXT NOT NULL DEFAULT 'active',
+            estimated_hours REAL,
+            hours_per_day REAL,
+            hours_per_week REAL,
+            deadline TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (client_id) REFERENCES clients(id) ON DELETE SET NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS time_sessions (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            start_time TEXT NOT NULL,
+            end_time TEXT,
+            duration_seconds INTEGER,
+            notes TEXT,
+            is_running INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_projects_client_id ON projects(client_id);
+        CREATE INDEX IF NOT EXISTS idx_sessions_project_id ON time_sessions(project_id);
+        CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON time_sessions(start_time);
+        CREATE INDEX IF NOT EXISTS idx_sessions_is_running ON time_sessions(is_running);",
+    )
+}
+
+/// v2: key/value app settings (session policy and friends)
+fn v2_settings(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );",
+    )
+}
+
+/// v3: heartbeat for running sessions, used to recover from unclean exits
+fn v3_session_heartbeat(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE time_sessions ADD COLUMN last_heartbeat TEXT;
+         UPDATE time_sessions SET last_heartbeat = start_time WHERE is_running = 1;",
+    )
+}
+
+/// v4: hourly rates on clients (overridable per project) and billable sessions
+fn v4_billing(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE clients ADD COLUMN hourly_rate REAL;
+         ALTER TABLE clients ADD COLUMN currency TEXT;
+         ALTER TABLE projects ADD COLUMN hourly_rate REAL;
+         ALTER TABLE projects ADD COLUMN currency TEXT;
+         ALTER TABLE time_sessions ADD COLUMN billable INTEGER NOT NULL DEFAULT 1;",
+    )
+}
+
+/// v5: persisted invoices; invoiced sessions point at their invoice and are locked
+fn v5_invoices(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE invoices (
+            id TEXT PRIMARY KEY,
+            number TEXT NOT NULL UNIQUE,
+            client_id TEXT NOT NULL,
+            period_start TEXT NOT NULL,
+            period_end TEXT NOT NULL,
+            issue_date TEXT NOT NULL,
+            currency TEXT,
+            subtotal REAL NOT NULL,
+            tax_rate REAL NOT NULL DEFAULT 0,
+            tax_amount REAL NOT NULL,
+            total REAL NOT NULL,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (client_id) REFERENCES clients(id)
+        );
+
+        CREATE TABLE invoice_items (
+            id TEXT PRIMARY KEY,
+            invoice_id TEXT NOT NULL,
+            project_id TEXT,
+            description TEXT NOT NULL,
+            hours REAL NOT NULL,
+            rate REAL NOT NULL,
+            amount REAL NOT NULL,
+            FOREIGN KEY (invoice_id) REFERENCES invoices(id) ON DELETE CASCADE
+        );
+
+        ALTER TABLE time_sessions ADD COLUMN invoice_id TEXT REFERENCES invoices(id);
+        ALTER TABLE clients ADD COLUMN billing_address TEXT;
+        ALTER TABLE clients ADD COLUMN tax_id TEXT;
+
+        CREATE INDEX idx_invoices_client_id ON invoices(client_id);
+        CREATE INDEX idx_invoice_items_invoice_id ON invoice_items(invoice_id);
+        CREATE INDEX idx_sessions_invoice_id ON time_sessions(invoice_id);",
+    )
+}
+
+/// v6: tasks beneath projects; sessions may point at one
+fn v6_tasks(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE tasks (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            status TEXT NOT NULL DEFAULT 'todo',
+            estimated_hours REAL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        ALTER TABLE time_sessions ADD COLUMN task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL;
+
+        CREATE INDEX idx_tasks_project_id ON tasks(project_id);
+        CREATE INDEX idx_sessions_task_id ON time_sessions(task_id);",
+    )
+}
+
+/// v7: free-form tags on sessions (many-to-many)
+fn v7_tags(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE tags (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE session_tags (
+            session_id TEXT NOT NULL,
+            tag_id TEXT NOT NULL,
+            PRIMARY KEY (session_id, tag_id),
+            FOREIGN KEY (session_id) REFERENCES time_sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX idx_session_tags_tag_id ON session_tags(tag_id);",
+    )
+}
+
+/// v8: the IANA zone each session was recorded in, so its day never shifts when
+/// the user travels. Existing sessions are assumed to be from the current system zone.
+fn v8_session_timezone(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch("ALTER TABLE time_sessions ADD COLUMN timezone TEXT;")?;
+    tx.execute(
+        "UPDATE time_sessions SET timezone = ?1",
+        [crate::timezone::system_timezone().name()],
+    )?;
+    Ok(())
+}
+
+/// v9: estimate thresholds (75/90/100 %) already notified per project, so each
+/// alert fires once
+fn v9_budget_alerts(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE budget_alerts (
+            project_id TEXT NOT NULL,
+            threshold INTEGER NOT NULL,
+            notified_at TEXT NOT NULL,
+            PRIMARY KEY (project_id, threshold),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );",
+    )
+}
+
+/// v10: daily target notifications already sent, one per project, day and kind
+/// ("reached" or "at_risk")
+fn v10_target_alerts(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE target_alerts (
+            project_id TEXT NOT NULL,
+            date TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            notified_at TEXT NOT NULL,
+            PRIMARY KEY (project_id, date, kind),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );",
+    )
+}
+
+/// v11: focus intervals completed per session in pomodoro mode
+fn v11_session_pomodoros(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch("ALTER TABLE time_sessions ADD COLUMN pomodoros INTEGER NOT NULL DEFAULT 0;")
+}
+
+/// v12: pauses within a session; the open pause of a paused session has no `resumed_at`
+fn v12_session_pauses(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE session_pauses (
+            id TEXT PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            paused_at TEXT NOT NULL,
+            resumed_at TEXT,
+            reason TEXT NOT NULL DEFAULT 'manual',
+            FOREIGN KEY (session_id) REFERENCES time_sessions(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX idx_session_pauses_session_id ON session_pauses(session_id);",
+    )
+}
+
+/// v13: journal of destructive operations with before-images of the rows they
+/// touched, for undo/redo and the trash
+fn v13_operation_journal(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE operation_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            operation TEXT NOT NULL,
+            target_id TEXT NOT NULL,
+            label TEXT NOT NULL,
+            images TEXT NOT NULL,
+            undone_at TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX idx_operation_journal_created_at ON operation_journal(created_at);",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timer-count-migrations-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A database as an install at `version` left it, with one session in it
+    fn fixture_at(version: i32) -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+
+        // Installs from before versioning have the v1 tables at user_version 0
+        for migration in &MIGRATIONS[..version.max(1) as usize] {
+            let tx = conn.transaction().unwrap();
+            migration(&tx).unwrap();
+            tx.commit().unwrap();
+        }
+        conn.pragma_update(None, "user_version", version).unwrap();
+
+        conn.execute_batch(
+            "INSERT INTO clients (id, name, created_at, updated_at)
+             VALUES ('c1', 'Client', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z');
+             INSERT INTO projects (id, name, client_id, created_at, updated_at)
+             VALUES ('p1', 'Project', 'c1', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z');
+             INSERT INTO time_sessions (id, project_id, start_time, end_time, duration_seconds, is_running,
+             created_at, updated_at)
+             VALUES ('s1', 'p1', '2024-01-01T09:00:00Z', '2024-01-01T10:00:00Z', 3600, 0,
+             '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table)).unwrap();
+        let columns = stmt.query_map([], |row| row.get(1)).unwrap();
+        columns.collect::<Result<_, _>>().unwrap()
+    }
+
+    fn assert_latest_schema(conn: &Connection) {
+        assert_eq!(schema_version(conn).unwrap(), LATEST_VERSION);
+
+        let expected = [
+            ("clients", &["name", "color"][..]),
+            ("projects", &["client_id", "status", "deadline"][..]),
+            ("time_sessions", &["project_id", "start_time", "end_time", "duration_seconds", "is_running"][..]),
+            ("settings", &["key", "value"][..]),
+            ("time_sessions", &["last_heartbeat"][..]),
+            ("clients", &["hourly_rate", "currency"][..]),
+            ("projects", &["hourly_rate", "currency"][..]),
+            ("time_sessions", &["billable"][..]),
+            ("invoices", &["number", "client_id", "total"][..]),
+            ("invoice_items", &["invoice_id", "amount"][..]),
+            ("time_sessions", &["invoice_id"][..]),
+            ("clients", &["billing_address", "tax_id"][..]),
+            ("tasks", &["project_id", "status"][..]),
+            ("time_sessions", &["task_id"][..]),
+            ("tags", &["name", "color"][..]),
+            ("session_tags", &["session_id", "tag_id"][..]),
+            ("time_sessions", &["timezone"][..]),
+            ("budget_alerts", &["project_id", "threshold"][..]),
+            ("target_alerts", &["project_id", "date", "kind"][..]),
+            ("time_sessions", &["pomodoros"][..]),
+            ("session_pauses", &["session_id", "paused_at", "resumed_at", "reason"][..]),
+            ("operation_journal", &["operation", "images", "undone_at"][..]),
+        ];
+        for (table, expected_columns) in expected {
+            let actual = columns(conn, table);
+            for column in expected_columns {
+                assert!(actual.iter().any(|c| c == column), "{}.{} is missing", table, column);
+            }
+        }
+    }
+
+    #[test]
+    fn upgrades_every_historical_version() {
+        for version in 0..LATEST_VERSION {
+            let mut conn = fixture_at(version);
+            let backup_dir = temp_dir();
+
+            run_migrations(&mut conn, &backup_dir).unwrap_or_else(|e| panic!("from v{}: {}", version, e));
+
+            assert_latest_schema(&conn);
+            let duration: i64 = conn
+                .query_row("SELECT duration_seconds FROM time_sessions WHERE id = 's1'", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(duration, 3600, "session data lost from v{}", version);
+
+            std::fs::remove_dir_all(backup_dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn creates_latest_schema_on_empty_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let backup_dir = temp_dir();
+
+        run_migrations(&mut conn, &backup_dir).unwrap();
+
+        assert_latest_schema(&conn);
+        // Nothing to lose, so nothing is backed up
+        assert_eq!(std::fs::read_dir(&backup_dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(backup_dir).unwrap();
+    }
+
+    #[test]
+    fn backs_up_before_migrating() {
+        let mut conn = fixture_at(0);
+        let backup_dir = temp_dir();
+
+        run_migrations(&mut conn, &backup_dir).unwrap();
+
+        let backups: Vec<PathBuf> = std::fs::read_dir(&backup_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(backups.len(), 1);
+        let file_name = backups[0].file_name().unwrap().to_string_lossy().into_owned();
+        assert!(file_name.starts_with("timer_count-v0-"), "{}", file_name);
+
+        // The copy is the database as it was before the upgrade
+        let backup = Connection::open(&backups[0]).unwrap();
+        assert_eq!(schema_version(&backup).unwrap(), 0);
+        let sessions: i64 = backup
+            .query_row("SELECT COUNT(*) FROM time_sessions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sessions, 1);
+
+        std::fs::remove_dir_all(backup_dir).unwrap();
+    }
+
+    #[test]
+    fn failing_step_rolls_back() {
+        fn broken(tx: &Transaction) -> rusqlite::Result<()> {
+            tx.execute_batch(
+                "CREATE TABLE half_done (id TEXT PRIMARY KEY);
+                 ALTER TABLE no_such_table ADD COLUMN oops TEXT;",
+            )
+        }
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        let backup_dir = temp_dir();
+
+        let result = apply_migrations(&mut conn, &backup_dir, &[v1_initial_schema, broken]);
+
+        assert!(result.unwrap_err().starts_with("Migration to v2 failed"));
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        assert!(columns(&conn, "half_done").is_empty());
+        assert!(!columns(&conn, "time_sessions").is_empty());
+
+        // Once the step is fixed the upgrade picks up where it stopped
+        apply_migrations(&mut conn, &backup_dir, &[v1_initial_schema, |_| Ok(())]).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 2);
+
+        std::fs::remove_dir_all(backup_dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", LATEST_VERSION + 1).unwrap();
+
+        assert!(run_migrations(&mut conn, &std::env::temp_dir()).is_err());
+    }
+}
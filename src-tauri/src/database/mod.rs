@@ -1,7 +1,9 @@
-use rusqlite::{Connection, Result};
+pub mod migrations;
+
+use rusqlite::Connection;
 use tauri::Manager;
 
-pub fn init_database(app_handle: &tauri::AppHandle) -> Result<Connection> {
+pub fn init_database(app_handle: &tauri::AppHandle) -> Result<Connection, String> {
     let app_dir = app_handle
         .path()
         .app_data_dir()
@@ -10,85 +12,62 @@ pub fn init_database(app_handle: &tauri::AppHandle) -> Result<Connection> {
     std::fs::create_dir_all(&app_dir).expect("Failed to create app directory");
 
     let db_path = app_dir.join("timer_count.db");
-    let conn = Connection::open(db_path)?;
+    let mut conn = Connection::open(db_path).map_err(|e| e.to_string())?;
 
-    create_tables(&conn)?;
+    configure_connection(&conn).map_err(|e| e.to_string())?;
+    migrations::run_migrations(&mut conn, &app_dir.join("backups"))?;
 
     Ok(conn)
 }
 
-fn create_tables(conn: &Connection) -> Result<()> {
-    // Clients table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS clients (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            description TEXT,
-            color TEXT,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL
-        )",
-        [],
-    )?;
-
-    // Projects table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS projects (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            description TEXT,
-            client_id TEXT,
-            color TEXT,
-            priority INTEGER NOT NULL DEFAULT 2,
-            status TEXT NOT NULL DEFAULT 'active',
-            estimated_hours REAL,
-            hours_per_day REAL,
-            hours_per_week REAL,
-            deadline TEXT,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            FOREIGN KEY (client_id) REFERENCES clients(id) ON DELETE SET NULL
-        )",
-        [],
-    )?;
+/// Per-connection setup: our SQL functions, and foreign key enforcement, which
+/// SQLite leaves off unless asked so the schema's cascades would never run
+fn configure_connection(conn: &Connection) -> rusqlite::Result<()> {
+    crate::timezone::register_sql_functions(conn)?;
+    conn.pragma_update(None, "foreign_keys", true)
+}
 
-    // Time sessions table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS time_sessions (
-            id TEXT PRIMARY KEY,
-            project_id TEXT NOT NULL,
-            start_time TEXT NOT NULL,
-            end_time TEXT,
-            duration_seconds INTEGER,
-            notes TEXT,
-            is_running INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
+/// A fresh in-memory database at the latest schema, set up like the app's own
+#[cfg(test)]
+pub(crate) fn test_connection() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    configure_connection(&conn).unwrap();
+    migrations::run_migrations(&mut conn, &std::env::temp_dir()).unwrap();
+    conn
+}
 
-    // Create indexes for better query performance
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_projects_client_id ON projects(client_id)",
-        [],
-    )?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_sessions_project_id ON time_sessions(project_id)",
-        [],
-    )?;
+    #[test]
+    fn foreign_keys_are_enforced() {
+        let conn = test_connection();
+        conn.execute_batch(
+            "INSERT INTO clients (id, name, created_at, updated_at) VALUES ('c1', 'Client', '', '');
+             INSERT INTO projects (id, name, client_id, created_at, updated_at) VALUES ('p1', 'Project', 'c1', '', '');
+             INSERT INTO time_sessions (id, project_id, start_time, created_at, updated_at)
+             VALUES ('s1', 'p1', '2024-01-01T09:00:00Z', '', '');",
+        )
+        .unwrap();
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON time_sessions(start_time)",
-        [],
-    )?;
+        let orphan = conn.execute(
+            "INSERT INTO time_sessions (id, project_id, start_time, created_at, updated_at)
+             VALUES ('s2', 'missing', '2024-01-01T09:00:00Z', '', '')",
+            [],
+        );
+        assert!(orphan.is_err());
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_sessions_is_running ON time_sessions(is_running)",
-        [],
-    )?;
+        conn.execute("DELETE FROM clients WHERE id = 'c1'", []).unwrap();
+        let client_id: Option<String> = conn
+            .query_row("SELECT client_id FROM projects WHERE id = 'p1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(client_id, None);
 
-    Ok(())
+        conn.execute("DELETE FROM projects WHERE id = 'p1'", []).unwrap();
+        let sessions: i64 = conn
+            .query_row("SELECT COUNT(*) FROM time_sessions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sessions, 0);
+    }
 }
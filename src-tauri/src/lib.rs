@@ -1,14 +1,29 @@
 mod commands;
 mod database;
 mod models;
+mod pdf_layout;
 mod tray_manager;
 mod system_events;
+mod session_recovery;
+mod timezone;
 
 use std::sync::Mutex;
 use tauri::Manager;
 use tauri::tray::TrayIconBuilder;
 use tauri::Emitter;
 
+fn is_single_session_policy<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    let db = app.state::<Mutex<rusqlite::Connection>>();
+    let Ok(conn) = db.lock() else {
+        return false;
+    };
+
+    matches!(
+        commands::settings::read_setting(&conn, commands::settings::SESSION_POLICY),
+        Ok(Some(policy)) if policy == "one_global"
+    )
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -28,6 +43,9 @@ pub fn run() {
             let conn = database::init_database(app.handle())?;
             app.manage(Mutex::new(conn));
 
+            // Close sessions left running by a crash before anything reads them
+            session_recovery::setup_session_recovery(app);
+
             // Build initial tray menu with active projects only (fallback before React loads)
             let app_handle = app.handle();
             let projects: Vec<_> = commands::projects::get_all_projects(
@@ -66,6 +84,17 @@ pub fn run() {
                             }
                             app.exit(0);
                         }
+                        "reminder_action" => {
+                            let app_handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = commands::reminders::act_on_pending_reminder(&app_handle) {
+                                    eprintln!("Reminder action failed: {}", e);
+                                }
+                                if let Some(window) = app_handle.get_webview_window("main") {
+                                    let _ = window.emit("tray-project-toggled", ());
+                                }
+                            });
+                        }
                         id if id.starts_with("project_") => {
                             // Extract project ID and handle toggle
                             let project_id = id.trim_start_matches("project_").to_string();
@@ -91,11 +120,23 @@ pub fn run() {
                                         session.id.clone(),
                                         None,
                                     );
+                                } else if let Some(current) = running_sessions.first().filter(|_| {
+                                    is_single_session_policy(&app_handle)
+                                }) {
+                                    // Only one timer may run: hand over atomically
+                                    let _ = commands::sessions::switch_session(
+                                        app_handle.state::<Mutex<rusqlite::Connection>>(),
+                                        current.id.clone(),
+                                        project_id.clone(),
+                                        None,
+                                        None,
+                                    );
                                 } else {
                                     // Start a new session
                                     let _ = commands::sessions::start_session(
                                         app_handle.state::<Mutex<rusqlite::Connection>>(),
                                         project_id.clone(),
+                                        None,
                                     );
                                 }
 
@@ -121,6 +162,24 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // Scheduled full backups with rotation
+            commands::backup::setup_automatic_backups(app);
+
+            // Notifications when projects cross 75/90/100 % of their estimate
+            commands::budget::setup_budget_alerts(app);
+
+            // Notifications when daily targets are reached or out of reach
+            commands::targets::setup_target_alerts(app);
+
+            // Pomodoro engine: phase changes and countdown in the tray title
+            commands::pomodoro::setup_pomodoro(app);
+
+            // Reminders to start a timer during working hours or stop a runaway one
+            commands::reminders::setup_reminders(app);
+
+            // Purge deleted items whose trash retention has run out
+            commands::journal::setup_trash_purge(app);
+
             // Setup system event listeners for detecting sleep/lock
             system_events::setup_system_event_listeners(app);
 
@@ -139,35 +198,108 @@ pub fn run() {
             commands::clients::get_client,
             commands::clients::create_client,
             commands::clients::update_client,
+            commands::clients::update_client_billing,
             commands::clients::delete_client,
             // Project commands
             commands::projects::get_all_projects,
             commands::projects::get_project,
             commands::projects::create_project,
             commands::projects::update_project,
+            commands::projects::update_project_billing,
             commands::projects::delete_project,
             // Session commands
             commands::sessions::get_all_sessions,
             commands::sessions::get_running_sessions,
             commands::sessions::get_project_sessions,
             commands::sessions::start_session,
+            commands::sessions::switch_session,
             commands::sessions::stop_session,
+            commands::sessions::pause_session,
+            commands::sessions::resume_session,
+            commands::sessions::get_session_pauses,
             commands::sessions::update_session_notes,
             commands::sessions::update_session,
+            commands::sessions::create_manual_session,
+            commands::sessions::create_manual_sessions,
+            commands::sessions::split_session,
+            commands::sessions::merge_sessions,
+            commands::sessions::set_session_billable,
+            commands::sessions::set_session_task,
             commands::sessions::stop_all_running_sessions,
             commands::sessions::delete_session,
+            commands::sessions::resolve_idle_time,
+            commands::sessions::get_orphaned_sessions,
+            commands::sessions::resolve_orphaned_session,
+            // Task commands
+            commands::tasks::get_project_tasks,
+            commands::tasks::create_task,
+            commands::tasks::update_task,
+            commands::tasks::delete_task,
+            // Tag commands
+            commands::tags::get_all_tags,
+            commands::tags::create_tag,
+            commands::tags::update_tag,
+            commands::tags::delete_tag,
+            commands::tags::get_session_tags,
+            commands::tags::get_session_tag_map,
+            commands::tags::set_session_tags,
+            // Overlap commands
+            commands::overlaps::get_session_overlaps,
+            commands::overlaps::resolve_session_overlap,
             // Stats commands
             commands::stats::get_project_stats,
             commands::stats::get_all_projects_stats,
             commands::stats::get_daily_stats,
             commands::stats::get_date_range_stats,
+            commands::stats::get_period_stats,
+            commands::stats::get_billing_stats,
+            commands::stats::get_task_breakdown,
+            commands::stats::get_tag_stats,
+            // Budget commands
+            commands::budget::get_budget_status,
+            commands::budget::get_all_budget_statuses,
+            commands::budget::get_budget_burndown,
+            // Target commands
+            commands::targets::get_target_progress,
+            // Pomodoro commands
+            commands::pomodoro::start_pomodoro,
+            commands::pomodoro::stop_pomodoro,
+            commands::pomodoro::skip_pomodoro_phase,
+            commands::pomodoro::get_pomodoro_status,
+            // Reminder commands
+            commands::reminders::get_pending_reminder,
+            commands::reminders::act_on_reminder,
+            commands::reminders::dismiss_reminder,
             // Tray commands
             commands::tray::update_tray_menu,
             // Export commands
             commands::export::export_daily_backup,
             commands::export::save_daily_backup,
             commands::export::generate_pdf_report,
+            commands::export::export_sessions,
             commands::export::get_current_month_range,
+            // Backup commands
+            commands::backup::create_full_backup,
+            commands::backup::preview_backup,
+            commands::backup::restore_backup,
+            // Undo and trash commands
+            commands::journal::undo_operation,
+            commands::journal::redo_operation,
+            commands::journal::get_operation_history,
+            commands::journal::get_trash,
+            commands::journal::restore_from_trash,
+            commands::journal::empty_trash,
+            // Import commands
+            commands::import::import_time_entries,
+            // Settings commands
+            commands::settings::get_settings,
+            commands::settings::set_setting,
+            // Invoice commands
+            commands::invoices::create_invoice,
+            commands::invoices::get_invoices,
+            commands::invoices::get_invoice,
+            commands::invoices::export_invoice_pdf,
+            commands::invoices::void_invoice,
             // Notion commands
             commands::notion::sync_sessions_to_notion,
         ])
@@ -7,6 +7,10 @@ pub struct Client {
     pub name: String,
     pub description: Option<String>,
     pub color: Option<String>,
+    pub hourly_rate: Option<f64>,
+    pub currency: Option<String>, // ISO 4217, e.g. "EUR"
+    pub billing_address: Option<String>,
+    pub tax_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -24,6 +28,8 @@ pub struct Project {
     pub hours_per_day: Option<f64>,
     pub hours_per_week: Option<f64>,
     pub deadline: Option<DateTime<Utc>>,
+    pub hourly_rate: Option<f64>, // overrides the client's rate when set
+    pub currency: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -37,6 +43,38 @@ pub struct TimeSession {
     pub duration_seconds: Option<i64>,
     pub notes: Option<String>,
     pub is_running: bool,
+    pub billable: bool,
+    pub invoice_id: Option<String>, // set once invoiced; the session is then locked
+    pub task_id: Option<String>,
+    pub timezone: Option<String>, // IANA zone the session was recorded in
+    #[serde(default)]
+    pub pomodoros: i32, // focus intervals completed in pomodoro mode
+    #[serde(default)]
+    pub paused_at: Option<DateTime<Utc>>, // set while a running session is paused
+    #[serde(default)]
+    pub paused_seconds: i64, // time spent in finished pauses
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A pause within a session; `resumed_at` is empty while it lasts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPause {
+    pub id: String,
+    pub session_id: String,
+    pub paused_at: DateTime<Utc>,
+    pub resumed_at: Option<DateTime<Utc>>,
+    pub reason: String, // "manual", "screen_lock" or "merge_gap"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub status: String, // "todo", "in_progress", "done"
+    pub estimated_hours: Option<f64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -47,6 +85,9 @@ pub struct ProjectStats {
     pub total_seconds: i64,
     pub total_hours: f64,
     pub session_count: i32,
+    /// Live time of running sessions included in the totals (0 unless requested)
+    #[serde(default)]
+    pub running_seconds: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +96,9 @@ pub struct DailyStats {
     pub total_seconds: i64,
     pub total_hours: f64,
     pub project_breakdown: Vec<ProjectTimeBreakdown>,
+    /// Live time of running sessions included in the totals (0 unless requested)
+    #[serde(default)]
+    pub running_seconds: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,3 +109,196 @@ pub struct ProjectTimeBreakdown {
     pub total_seconds: i64,
     pub total_hours: f64,
 }
+
+/// Time per client; projects without a client are grouped under `client_id = None`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientTimeBreakdown {
+    pub client_id: Option<String>,
+    pub client_name: Option<String>,
+    pub total_seconds: i64,
+    pub total_hours: f64,
+}
+
+/// Totals for one week, month, quarter or year, compared with the period before it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodStats {
+    pub period: String, // "2024-W05", "2024-03", "2024-Q1" or "2024"
+    pub start_date: String,
+    pub end_date: String,
+    pub total_seconds: i64,
+    pub total_hours: f64,
+    pub session_count: i32,
+    pub project_breakdown: Vec<ProjectTimeBreakdown>,
+    pub client_breakdown: Vec<ClientTimeBreakdown>,
+    pub previous_total_seconds: i64,
+    pub previous_total_hours: f64,
+    pub change_percent: Option<f64>, // None when the previous period has no time
+}
+
+/// Logged time against a project's estimate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub project_id: String,
+    pub project_name: String,
+    pub estimated_hours: Option<f64>,
+    pub consumed_seconds: i64, // includes running sessions
+    pub consumed_hours: f64,
+    pub remaining_hours: Option<f64>,
+    pub percent_used: Option<f64>,
+    pub velocity_hours_per_day: f64, // average over the recent window
+    pub projected_completion_date: Option<String>,
+    pub deadline: Option<DateTime<Utc>>,
+    pub on_track: Option<bool>, // projected completion on or before the deadline
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnDownPoint {
+    pub date: String,
+    pub consumed_hours: f64,
+    pub remaining_hours: Option<f64>,
+}
+
+/// Today's and this week's logged time against a project's hours_per_day /
+/// hours_per_week. Streaks count consecutive days (weeks) the target was met.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetProgress {
+    pub project_id: String,
+    pub project_name: String,
+    pub daily_target_hours: Option<f64>,
+    pub today_hours: f64,
+    pub today_remaining_hours: Option<f64>,
+    pub today_percent: Option<f64>,
+    pub daily_streak: i32,
+    pub weekly_target_hours: Option<f64>,
+    pub week_start_date: String,
+    pub week_hours: f64,
+    pub week_remaining_hours: Option<f64>,
+    pub week_percent: Option<f64>,
+    pub weekly_streak: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroStatus {
+    pub project_id: String,
+    pub task_id: Option<String>,
+    pub phase: String, // "focus", "short_break", "long_break"
+    pub phase_ends_at: DateTime<Utc>,
+    pub remaining_seconds: i64,
+    pub completed_pomodoros: u32,
+    pub session_id: Option<String>, // the running session while in focus
+}
+
+/// A reminder waiting for the user: "start" offers to resume the last project,
+/// "stop" offers to stop a session that has run past the configured maximum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub kind: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub task_id: Option<String>,
+    pub session_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Time per tag; a session with several tags counts towards each of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagTimeBreakdown {
+    pub tag_id: String,
+    pub tag_name: String,
+    pub color: Option<String>,
+    pub total_seconds: i64,
+    pub total_hours: f64,
+    pub session_count: i32,
+}
+
+/// Time per task of a project; `task_id` is None for sessions without a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskTimeBreakdown {
+    pub project_id: String,
+    pub project_name: String,
+    pub task_id: Option<String>,
+    pub task_name: Option<String>,
+    pub status: Option<String>,
+    pub estimated_hours: Option<f64>,
+    pub total_seconds: i64,
+    pub total_hours: f64,
+    pub session_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleSessionSpan {
+    pub session_id: String,
+    pub project_id: String,
+    pub idle_start: DateTime<Utc>,
+    pub idle_end: DateTime<Utc>,
+    pub idle_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlePeriod {
+    pub idle_start: DateTime<Utc>,
+    pub idle_end: DateTime<Utc>,
+    pub sessions: Vec<IdleSessionSpan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedSession {
+    pub session: TimeSession,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBillingBreakdown {
+    #[serde(flatten)]
+    pub time: ProjectTimeBreakdown,
+    pub client_id: Option<String>,
+    pub billable_seconds: i64,
+    pub billable_hours: f64,
+    pub hourly_rate: Option<f64>,
+    pub currency: Option<String>,
+    pub amount: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invoice {
+    pub id: String,
+    pub number: String,
+    pub client_id: String,
+    pub period_start: String,
+    pub period_end: String,
+    pub issue_date: String,
+    pub currency: Option<String>,
+    pub subtotal: f64,
+    pub tax_rate: f64, // percentage, e.g. 21.0
+    pub tax_amount: f64,
+    pub total: f64,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceItem {
+    pub id: String,
+    pub invoice_id: String,
+    pub project_id: Option<String>,
+    pub description: String,
+    pub hours: f64,
+    pub rate: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceDetail {
+    pub invoice: Invoice,
+    pub items: Vec<InvoiceItem>,
+    pub session_count: i32,
+}
@@ -0,0 +1,382 @@
+use printpdf::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+
+// A4 portrait, all measures in mm
+pub const PAGE_WIDTH: f32 = 210.0;
+pub const PAGE_HEIGHT: f32 = 297.0;
+pub const MARGIN_LEFT: f32 = 20.0;
+pub const MARGIN_RIGHT: f32 = 190.0;
+const CONTENT_TOP: f32 = 270.0;
+const CONTENT_BOTTOM: f32 = 22.0;
+const PT_TO_MM: f32 = 0.352_778;
+
+static DEJAVU_SANS: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+static DEJAVU_SANS_BOLD: &[u8] = include_bytes!("../fonts/DejaVuSans-Bold.ttf");
+
+/// Fonts with glyphs DejaVu lacks (emoji such as 📝). Only TrueType outlines
+/// can be embedded, so color-bitmap emoji fonts are not listed.
+const SYMBOL_FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/TTF/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/truetype/ancient-scripts/Symbola_hint.ttf",
+    "/usr/share/fonts/TTF/Symbola.ttf",
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+];
+
+#[derive(Clone, Copy)]
+pub enum FontStyle {
+    Regular,
+    Bold,
+}
+
+struct Font {
+    pdf: IndirectFontRef,
+    // Horizontal advance of every character the font maps, read once at load
+    // since wrapping measures text glyph by glyph
+    advances: HashMap<char, u16>,
+    units_per_em: f32,
+}
+
+impl Font {
+    fn load(doc: &PdfDocumentReference, data: &[u8]) -> Result<Self, String> {
+        let face = ttf_parser::Face::parse(data, 0).map_err(|e| e.to_string())?;
+
+        let mut advances = HashMap::new();
+        let subtables = face.tables().cmap.into_iter().flat_map(|cmap| cmap.subtables);
+        for subtable in subtables.filter(|subtable| subtable.is_unicode()) {
+            subtable.codepoints(|code_point| {
+                let Some(c) = char::from_u32(code_point) else {
+                    return;
+                };
+                if let Some(glyph) = subtable.glyph_index(code_point) {
+                    advances.entry(c).or_insert(face.glyph_hor_advance(glyph).unwrap_or(0));
+                }
+            });
+        }
+
+        let pdf = doc.add_external_font(data).map_err(|e| e.to_string())?;
+        Ok(Font {
+            pdf,
+            advances,
+            units_per_em: face.units_per_em() as f32,
+        })
+    }
+
+    fn covers(&self, c: char) -> bool {
+        c.is_control() || self.advances.contains_key(&c)
+    }
+
+    /// Width of `text` in mm at `size` pt
+    fn width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = text
+            .chars()
+            .filter_map(|c| self.advances.get(&c))
+            .map(|&advance| u32::from(advance))
+            .sum();
+        units as f32 / self.units_per_em * size * PT_TO_MM
+    }
+}
+
+/// Flowing page layout on top of printpdf: tracks the vertical cursor, breaks
+/// pages, repeats the header, numbers the pages and wraps text by measured width.
+pub struct PdfLayout {
+    doc: PdfDocumentReference,
+    regular: Font,
+    bold: Font,
+    symbols: Option<Font>,
+    pages: Vec<PdfPageIndex>,
+    layer: PdfLayerReference,
+    header: String,
+    y: f32,
+}
+
+impl PdfLayout {
+    pub fn new(title: &str, header: &str) -> Result<Self, String> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Capa 1");
+
+        let regular = Font::load(&doc, DEJAVU_SANS)?;
+        let bold = Font::load(&doc, DEJAVU_SANS_BOLD)?;
+        let symbols = SYMBOL_FONT_CANDIDATES
+            .iter()
+            .filter_map(|path| std::fs::read(path).ok())
+            .find(|data| {
+                ttf_parser::Face::parse(data, 0).is_ok_and(|face| face.tables().glyf.is_some())
+            })
+            .and_then(|data| Font::load(&doc, &data).ok());
+
+        let layer = doc.get_page(page).get_layer(layer);
+        let layout = PdfLayout {
+            doc,
+            regular,
+            bold,
+            symbols,
+            pages: vec![page],
+            layer,
+            header: header.to_string(),
+            y: CONTENT_TOP,
+        };
+        layout.draw_header();
+        Ok(layout)
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn advance(&mut self, mm: f32) {
+        self.y -= mm;
+    }
+
+    /// Start a new page unless `height` mm still fit above the footer
+    pub fn ensure_space(&mut self, height: f32) {
+        if self.y - height < CONTENT_BOTTOM {
+            self.new_page();
+        }
+    }
+
+    pub fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Capa");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.pages.push(page);
+        self.y = CONTENT_TOP;
+        self.draw_header();
+    }
+
+    fn draw_header(&self) {
+        if self.header.is_empty() {
+            return;
+        }
+        let gray = Color::Rgb(Rgb::new(0.55, 0.55, 0.55, None));
+        self.draw_text(&self.layer, &self.header, 8.0, MARGIN_LEFT, PAGE_HEIGHT - 15.0, FontStyle::Regular, &gray);
+        self.draw_rule(&self.layer, PAGE_HEIGHT - 17.0, MARGIN_LEFT, MARGIN_RIGHT, &gray, 0.3);
+    }
+
+    fn font(&self, style: FontStyle) -> &Font {
+        match style {
+            FontStyle::Regular => &self.regular,
+            FontStyle::Bold => &self.bold,
+        }
+    }
+
+    /// Split `text` into runs drawable by one font each, substituting glyphs
+    /// no embedded font has.
+    fn runs(&self, text: &str, style: FontStyle) -> Vec<(&Font, String)> {
+        let primary = self.font(style);
+        let mut runs: Vec<(&Font, String)> = Vec::new();
+
+        for c in text.chars() {
+            let (font, c) = if primary.covers(c) {
+                (primary, c)
+            } else if let Some(symbols) = self.symbols.as_ref().filter(|f| f.covers(c)) {
+                (symbols, c)
+            } else {
+                (primary, substitute_glyph(c))
+            };
+
+            match runs.last_mut() {
+                Some((run_font, run)) if std::ptr::eq(*run_font, font) => run.push(c),
+                _ => runs.push((font, c.to_string())),
+            }
+        }
+
+        runs
+    }
+
+    pub fn text_width(&self, text: &str, size: f32, style: FontStyle) -> f32 {
+        self.runs(text, style)
+            .iter()
+            .map(|(font, run)| font.width(run, size))
+            .sum()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text(
+        &self,
+        layer: &PdfLayerReference,
+        text: &str,
+        size: f32,
+        x: f32,
+        y: f32,
+        style: FontStyle,
+        color: &Color,
+    ) {
+        layer.set_fill_color(color.clone());
+        let mut x = x;
+        for (font, run) in self.runs(text, style) {
+            layer.use_text(run.as_str(), size, Mm(x), Mm(y), &font.pdf);
+            x += font.width(&run, size);
+        }
+    }
+
+    fn draw_rule(&self, layer: &PdfLayerReference, y: f32, x1: f32, x2: f32, color: &Color, thickness: f32) {
+        layer.set_outline_color(color.clone());
+        layer.set_outline_thickness(thickness);
+        layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(x1), Mm(y)), false),
+                (Point::new(Mm(x2), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    /// Draw a single line at the cursor (offset by `dy` mm) without moving it
+    pub fn text(&self, text: &str, size: f32, x: f32, dy: f32, style: FontStyle, color: &Color) {
+        self.draw_text(&self.layer, text, size, x, self.y + dy, style, color);
+    }
+
+    /// Like `text`, but the line ends at `right_x`
+    pub fn text_right(&self, text: &str, size: f32, right_x: f32, dy: f32, style: FontStyle, color: &Color) {
+        let x = right_x - self.text_width(text, size, style);
+        self.draw_text(&self.layer, text, size, x, self.y + dy, style, color);
+    }
+
+    /// Horizontal rule at the cursor (offset by `dy` mm)
+    pub fn rule(&self, dy: f32, x1: f32, x2: f32, color: &Color, thickness: f32) {
+        self.draw_rule(&self.layer, self.y + dy, x1, x2, color, thickness);
+    }
+
+    /// Break `text` into lines no wider than `max_width` mm
+    pub fn wrap(&self, text: &str, size: f32, style: FontStyle, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+
+                if self.text_width(&candidate, size, style) <= max_width {
+                    line = candidate;
+                    continue;
+                }
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                // A single word wider than the column is broken by characters
+                for c in word.chars() {
+                    line.push(c);
+                    if self.text_width(&line, size, style) > max_width && line.chars().count() > 1 {
+                        line.pop();
+                        lines.push(std::mem::replace(&mut line, c.to_string()));
+                    }
+                }
+            }
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    /// Wrapped text starting at the cursor; moves the cursor below it and
+    /// breaks pages between lines as needed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn paragraph(
+        &mut self,
+        text: &str,
+        size: f32,
+        x: f32,
+        max_width: f32,
+        line_height: f32,
+        style: FontStyle,
+        color: &Color,
+    ) {
+        for line in self.wrap(text, size, style, max_width) {
+            self.ensure_space(line_height);
+            self.text(&line, size, x, 0.0, style, color);
+            self.y -= line_height;
+        }
+    }
+
+    /// Stamp "Página n de N" on every page and write the file
+    pub fn save(self, file_path: &str) -> Result<(), String> {
+        let gray = Color::Rgb(Rgb::new(0.55, 0.55, 0.55, None));
+        let total = self.pages.len();
+
+        for (index, page) in self.pages.iter().enumerate() {
+            let footer_layer = self.doc.get_page(*page).add_layer("Pie de página");
+            let label = format!("Página {} de {}", index + 1, total);
+            let x = MARGIN_RIGHT - self.text_width(&label, 8.0, FontStyle::Regular);
+            self.draw_rule(&footer_layer, 15.0, MARGIN_LEFT, MARGIN_RIGHT, &gray, 0.3);
+            self.draw_text(&footer_layer, &label, 8.0, x, 10.0, FontStyle::Regular, &gray);
+        }
+
+        let file = File::create(file_path).map_err(|e| e.to_string())?;
+        self.doc
+            .save(&mut BufWriter::new(file))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Stand-ins for glyphs when no embedded font can draw the original
+fn substitute_glyph(c: char) -> char {
+    match c {
+        '📝' => '✎',
+        '✅' | '✔' => '✓',
+        '⏱' | '⏰' => '◷',
+        _ => '?',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> PdfLayout {
+        PdfLayout::new("Test", "").unwrap()
+    }
+
+    #[test]
+    fn width_matches_the_font_metrics() {
+        let layout = layout();
+        let face = ttf_parser::Face::parse(DEJAVU_SANS, 0).unwrap();
+        let advance = face.glyph_hor_advance(face.glyph_index('M').unwrap()).unwrap();
+        let expected = advance as f32 / face.units_per_em() as f32 * 10.0 * PT_TO_MM;
+
+        assert!((layout.text_width("M", 10.0, FontStyle::Regular) - expected).abs() < 1e-4);
+        assert!((layout.text_width("MM", 10.0, FontStyle::Regular) - 2.0 * expected).abs() < 1e-4);
+        assert!(layout.text_width("Mañana", 10.0, FontStyle::Bold) > layout.text_width("Mañana", 10.0, FontStyle::Regular));
+    }
+
+    #[test]
+    fn wrap_keeps_lines_within_width() {
+        let layout = layout();
+        let text = "Revisión del diseño con el cliente y ajustes de la maqueta según los comentarios recibidos";
+
+        let lines = layout.wrap(text, 10.0, FontStyle::Regular, 50.0);
+
+        assert!(lines.len() > 1);
+        for line in &lines {
+            assert!(layout.text_width(line, 10.0, FontStyle::Regular) <= 50.0, "{}", line);
+        }
+        assert_eq!(lines.join(" "), text);
+    }
+
+    #[test]
+    fn wrap_breaks_long_words_and_keeps_paragraphs() {
+        let layout = layout();
+
+        let lines = layout.wrap("abcdefghijklmnopqrstuvwxyz\n\nfin", 10.0, FontStyle::Regular, 20.0);
+
+        let blank = lines.iter().position(String::is_empty).unwrap();
+        assert!(blank > 1, "the long word should span several lines: {:?}", lines);
+        assert_eq!(lines[..blank].concat(), "abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(&lines[blank + 1..], ["fin"]);
+    }
+
+    #[test]
+    fn missing_glyphs_are_substituted() {
+        let layout = layout();
+        let runs = layout.runs("a\u{10FFFD}", FontStyle::Regular);
+
+        let text: String = runs.iter().map(|(_, run)| run.as_str()).collect();
+        assert_eq!(text, "a?");
+    }
+}
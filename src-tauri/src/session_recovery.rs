@@ -0,0 +1,75 @@
+use crate::commands::{sessions, settings};
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+const HEARTBEAT_INTERVAL_SECS: u64 = 30;
+
+static APP_STARTED_AT: OnceLock<DateTime<Utc>> = OnceLock::new();
+
+/// Sessions whose last heartbeat predates this instant were left running by a
+/// previous process.
+pub fn app_started_at() -> DateTime<Utc> {
+    *APP_STARTED_AT.get_or_init(Utc::now)
+}
+
+/// Close (or report) sessions orphaned by an unclean exit, then start heartbeating
+/// the sessions that belong to this process.
+pub fn setup_session_recovery<R: Runtime>(app: &tauri::App<R>) {
+    let started_at = app_started_at();
+    let app_handle = app.handle().clone();
+
+    if let Err(e) = recover_orphaned_sessions(&app_handle, started_at) {
+        eprintln!("Failed to recover orphaned sessions: {}", e);
+    }
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+
+        if let Some(db) = app_handle.try_state::<Mutex<Connection>>() {
+            if let Ok(conn) = db.lock() {
+                // Orphans keep their stale heartbeat until the user resolves them
+                let _ = conn.execute(
+                    "UPDATE time_sessions SET last_heartbeat = ?1
+                     WHERE is_running = 1 AND last_heartbeat >= ?2",
+                    rusqlite::params![&Utc::now().to_rfc3339(), &started_at.to_rfc3339()],
+                );
+            }
+        }
+    });
+}
+
+fn recover_orphaned_sessions<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    started_at: DateTime<Utc>,
+) -> Result<(), String> {
+    let db = app_handle.state::<Mutex<Connection>>();
+    let conn = db.lock().map_err(|e| e.to_string())?;
+
+    let orphaned = sessions::find_orphaned_sessions(&conn, started_at)?;
+    if orphaned.is_empty() {
+        return Ok(());
+    }
+
+    let ask = settings::read_setting(&conn, settings::ORPHAN_RECOVERY)?.as_deref() == Some("ask");
+
+    let mut unresolved = Vec::new();
+    for orphan in orphaned {
+        match orphan.last_heartbeat {
+            Some(last_heartbeat) if !ask => {
+                sessions::close_session_at(&conn, &orphan.session, last_heartbeat)?;
+                log::info!("Closed orphaned session {} at its last heartbeat", orphan.session.id);
+            }
+            // Without a heartbeat we cannot guess when it really stopped
+            _ => unresolved.push(orphan),
+        }
+    }
+
+    if !unresolved.is_empty() {
+        let _ = app_handle.emit("orphaned-sessions-detected", unresolved);
+    }
+
+    Ok(())
+}
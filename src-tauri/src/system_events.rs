@@ -8,6 +8,10 @@ pub fn setup_system_event_listeners<R: Runtime>(app: &tauri::App<R>) {
     // For macOS-specific system events (sleep, lock)
     #[cfg(target_os = "macos")]
     setup_macos_system_events(app_handle);
+
+    // On Linux, watch for idle time, screen lock and suspend
+    #[cfg(target_os = "linux")]
+    setup_linux_idle_watcher(app_handle);
 }
 
 #[cfg(target_os = "macos")]
@@ -35,23 +39,315 @@ fn setup_macos_system_events<R: Runtime>(app_handle: AppHandle<R>) {
 
                 if is_locked && !was_locked.load(Ordering::Relaxed) {
                     // Screen just got locked
-                    println!("Screen locked detected - stopping all running sessions");
-
-                    // Stop all running sessions in database
-                    if let Some(db) = app_handle.try_state::<Mutex<Connection>>() {
-                        if let Ok(_stopped) = crate::commands::sessions::stop_all_running_sessions(db.clone()) {
-                            println!("All running sessions stopped due to screen lock");
-                        }
-                    }
+                    println!("Screen locked detected - pausing all running sessions");
+                    set_lock_pause(&*app_handle, true);
 
                     let _ = app_handle.emit("screen-lock", ());
                     was_locked.store(true, Ordering::Relaxed);
                 } else if !is_locked && was_locked.load(Ordering::Relaxed) {
                     // Screen just got unlocked
-                    println!("Screen unlocked detected");
+                    println!("Screen unlocked detected - resuming sessions paused by the lock");
+                    set_lock_pause(&*app_handle, false);
+
+                    let _ = app_handle.emit("screen-unlock", ());
                     was_locked.store(false, Ordering::Relaxed);
                 }
             }
         }
     });
 }
+
+/// Pause the running sessions while the screen is locked, so the working block
+/// stays one session, and resume the ones the lock paused once it is unlocked
+#[cfg(target_os = "macos")]
+fn set_lock_pause<R: Runtime>(app_handle: &AppHandle<R>, locked: bool) {
+    use crate::commands::sessions;
+
+    let Some(db) = app_handle.try_state::<Mutex<Connection>>() else {
+        return;
+    };
+    let result = match db.lock() {
+        Ok(conn) if locked => sessions::pause_running_sessions(&conn, "screen_lock"),
+        Ok(conn) => sessions::resume_paused_sessions(&conn, "screen_lock"),
+        Err(e) => Err(e.to_string()),
+    };
+
+    match result {
+        Ok(_) => crate::tray_manager::refresh_tray_menu(app_handle),
+        Err(e) => eprintln!("Failed to update sessions for screen lock: {}", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+const POLL_INTERVAL_SECS: u64 = 5;
+/// Seconds without input before the user counts as idle
+#[cfg(target_os = "linux")]
+const IDLE_THRESHOLD_SECS: i64 = 5 * 60;
+
+/// Changes reported by logind over D-Bus
+#[cfg(target_os = "linux")]
+enum LoginEvent {
+    Sleeping(bool),
+    Locked(bool),
+}
+
+/// A stretch the user has been away for: idle, locked or asleep
+#[cfg(target_os = "linux")]
+struct Away {
+    since: chrono::DateTime<chrono::Utc>,
+    // The machine was asleep until then, so the user cannot have come back earlier
+    woke_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[cfg(target_os = "linux")]
+fn setup_linux_idle_watcher<R: Runtime>(app_handle: AppHandle<R>) {
+    use chrono::{Duration, Utc};
+    use std::sync::mpsc::{self, RecvTimeoutError};
+
+    let (sender, events) = mpsc::channel();
+    let login = dbus::LoginWatch::connect();
+    if let Some(login) = &login {
+        login.subscribe(sender);
+    }
+    let idle_monitor = dbus::IdleMonitor::connect();
+
+    std::thread::spawn(move || {
+        let mut away: Option<Away> = None;
+        let mut locked = login.as_ref().is_some_and(dbus::LoginWatch::locked);
+        let mut asleep = false;
+
+        loop {
+            // Lock and sleep changes wake the loop at once; idle time is polled
+            let poll_interval = std::time::Duration::from_secs(POLL_INTERVAL_SECS);
+            match events.recv_timeout(poll_interval) {
+                Ok(LoginEvent::Locked(value)) => locked = value,
+                Ok(LoginEvent::Sleeping(true)) => asleep = true,
+                Ok(LoginEvent::Sleeping(false)) => {
+                    asleep = false;
+                    if let Some(away) = &mut away {
+                        away.woke_at = Some(Utc::now());
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // Without logind only idle time is watched
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(poll_interval),
+            }
+            let now = Utc::now();
+
+            let idle_seconds = linux_idle_seconds(idle_monitor.as_ref(), login.as_ref()).unwrap_or(0);
+            let is_away = asleep || locked || idle_seconds >= IDLE_THRESHOLD_SECS;
+
+            match &away {
+                None if is_away => {
+                    log::info!("User away detected");
+                    let since = if asleep || locked { now } else { now - Duration::seconds(idle_seconds) };
+                    away = Some(Away { since, woke_at: None });
+                    let _ = app_handle.emit("system-idle", ());
+                }
+                Some(period) if !is_away => {
+                    log::info!("User returned");
+                    let returned_at = (now - Duration::seconds(idle_seconds))
+                        .max(period.woke_at.unwrap_or(period.since))
+                        .max(period.since);
+                    report_idle_period(&app_handle, period.since, returned_at);
+                    away = None;
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Tell the frontend which running sessions overlapped an idle period so the
+/// user can keep, discard or reassign that time (see `resolve_idle_time`).
+#[cfg(target_os = "linux")]
+fn report_idle_period<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    idle_start: chrono::DateTime<chrono::Utc>,
+    idle_end: chrono::DateTime<chrono::Utc>,
+) {
+    use crate::models::{IdlePeriod, IdleSessionSpan};
+
+    let Some(db) = app_handle.try_state::<Mutex<Connection>>() else {
+        return;
+    };
+    let Ok(running_sessions) = crate::commands::sessions::get_running_sessions(db) else {
+        return;
+    };
+
+    let sessions: Vec<IdleSessionSpan> = running_sessions
+        .into_iter()
+        .filter_map(|session| {
+            let span_start = idle_start.max(session.start_time);
+            (span_start < idle_end).then(|| IdleSessionSpan {
+                session_id: session.id,
+                project_id: session.project_id,
+                idle_start: span_start,
+                idle_end,
+                idle_seconds: (idle_end - span_start).num_seconds(),
+            })
+        })
+        .collect();
+
+    if sessions.is_empty() {
+        return;
+    }
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let _ = app_handle.emit(
+        "idle-period-detected",
+        IdlePeriod {
+            idle_start,
+            idle_end,
+            sessions,
+        },
+    );
+}
+
+/// Seconds since the last user input, from whichever source the desktop provides
+#[cfg(target_os = "linux")]
+fn linux_idle_seconds(idle_monitor: Option<&dbus::IdleMonitor>, login: Option<&dbus::LoginWatch>) -> Option<i64> {
+    if let Some(seconds) = idle_monitor.and_then(dbus::IdleMonitor::idle_seconds) {
+        return Some(seconds);
+    }
+
+    // logind fallback: IdleSinceHint is a wall-clock timestamp in microseconds
+    login?.idle_since().map(|idle_since_us| {
+        idle_since_us.map_or(0, |us| (chrono::Utc::now().timestamp_micros() - us).max(0) / 1_000_000)
+    })
+}
+
+/// logind and the desktop's idle monitor over D-Bus
+#[cfg(target_os = "linux")]
+mod dbus {
+    use super::LoginEvent;
+    use std::sync::mpsc::Sender;
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::OwnedObjectPath;
+
+    const SERVICE: &str = "org.freedesktop.login1";
+
+    pub(super) struct LoginWatch {
+        manager: Proxy<'static>,
+        session: Proxy<'static>,
+    }
+
+    impl LoginWatch {
+        /// Proxies for the logind manager and this process's session, or `None`
+        /// when there is no system bus or logind (containers, other init systems)
+        pub(super) fn connect() -> Option<Self> {
+            let connect = || -> zbus::Result<Self> {
+                let bus = Connection::system()?;
+                let manager = Proxy::new(&bus, SERVICE, "/org/freedesktop/login1", "org.freedesktop.login1.Manager")?;
+                let session_path: OwnedObjectPath = match std::env::var("XDG_SESSION_ID") {
+                    Ok(id) => manager.call("GetSession", &(id,))?,
+                    Err(_) => manager.call("GetSessionByPID", &(std::process::id(),))?,
+                };
+                let session = Proxy::new(&bus, SERVICE, session_path, "org.freedesktop.login1.Session")?;
+                Ok(Self { manager, session })
+            };
+
+            connect()
+                .map_err(|e| log::warn!("logind is not available, watching idle time only: {}", e))
+                .ok()
+        }
+
+        /// Forward `PrepareForSleep` and `LockedHint` changes to `sender`, each from
+        /// its own thread since the iterators block
+        pub(super) fn subscribe(&self, sender: Sender<LoginEvent>) {
+            match self.manager.receive_signal("PrepareForSleep") {
+                Ok(signals) => {
+                    let sender = sender.clone();
+                    std::thread::spawn(move || {
+                        for message in signals {
+                            // true when going to sleep, false once resumed
+                            let Ok(sleeping) = message.body().deserialize::<bool>() else {
+                                continue;
+                            };
+                            log::info!("{}", if sleeping { "Suspend detected" } else { "Resume from suspend detected" });
+                            if sender.send(LoginEvent::Sleeping(sleeping)).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Cannot watch for suspend: {}", e),
+            }
+
+            let changes = self.session.receive_property_changed::<bool>("LockedHint");
+            std::thread::spawn(move || {
+                for change in changes {
+                    let Ok(locked) = change.get() else {
+                        continue;
+                    };
+                    log::info!("{}", if locked { "Screen locked" } else { "Screen unlocked" });
+                    if sender.send(LoginEvent::Locked(locked)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        pub(super) fn locked(&self) -> bool {
+            self.session.get_property("LockedHint").unwrap_or(false)
+        }
+
+        /// When the session went idle, in microseconds since the epoch; `Some(None)`
+        /// when it is not idle
+        pub(super) fn idle_since(&self) -> Option<Option<i64>> {
+            let idle: bool = self.session.get_property("IdleHint").ok()?;
+            if !idle {
+                return Some(None);
+            }
+            let since: u64 = self.session.get_property("IdleSinceHint").ok()?;
+            Some(Some(since as i64))
+        }
+    }
+
+    /// The session bus service reporting time since the last input
+    pub(super) enum IdleMonitor {
+        /// GNOME, on Wayland and X11 alike
+        Mutter(Proxy<'static>),
+        /// KDE and the other desktops implementing the freedesktop screensaver API
+        ScreenSaver(Proxy<'static>),
+    }
+
+    impl IdleMonitor {
+        /// The first service that answers on the session bus, or `None` when neither does
+        pub(super) fn connect() -> Option<Self> {
+            let bus = Connection::session().ok()?;
+
+            let mutter = Proxy::new(
+                &bus,
+                "org.gnome.Mutter.IdleMonitor",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "org.gnome.Mutter.IdleMonitor",
+            )
+            .ok()
+            .map(Self::Mutter);
+            let screen_saver = || {
+                Proxy::new(&bus, "org.freedesktop.ScreenSaver", "/ScreenSaver", "org.freedesktop.ScreenSaver")
+                    .ok()
+                    .map(Self::ScreenSaver)
+            };
+
+            // Only keep a monitor if its service answers
+            mutter
+                .filter(|monitor| monitor.idle_seconds().is_some())
+                .or_else(|| screen_saver().filter(|monitor| monitor.idle_seconds().is_some()))
+        }
+
+        /// Both report milliseconds: `GetIdletime` as a u64, `GetSessionIdleTime` as a u32
+        pub(super) fn idle_seconds(&self) -> Option<i64> {
+            let ms = match self {
+                Self::Mutter(proxy) => proxy.call::<_, _, u64>("GetIdletime", &()).ok()?,
+                Self::ScreenSaver(proxy) => u64::from(proxy.call::<_, _, u32>("GetSessionIdleTime", &()).ok()?),
+            };
+            Some(ms as i64 / 1000)
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::Connection;
+
+/// The zone the operating system is set to, or UTC when it cannot be read
+pub fn system_timezone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("Unknown time zone: {}", name))
+}
+
+/// The configured `timezone` setting, falling back to the system zone
+pub fn user_timezone(conn: &Connection) -> Tz {
+    crate::commands::settings::read_setting(conn, crate::commands::settings::TIMEZONE)
+        .ok()
+        .flatten()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or_else(system_timezone)
+}
+
+/// Zone a session was recorded in; sessions from before zones were stored use `fallback`
+pub fn session_timezone(stored: Option<&str>, fallback: Tz) -> Tz {
+    stored.and_then(|name| name.parse().ok()).unwrap_or(fallback)
+}
+
+/// Calendar day of `instant` as seen in `tz`
+pub fn local_date(instant: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    instant.with_timezone(&tz).date_naive()
+}
+
+/// First instant of `date` in `tz`. Some zones skip midnight when DST starts,
+/// in which case the day begins at the first local time that exists.
+pub fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    (0..=3 * 60)
+        .find_map(|minutes| {
+            tz.from_local_datetime(&(midnight + Duration::minutes(minutes)))
+                .earliest()
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// Cut `[start, end)` at each local midnight in `tz`: one piece per calendar day covered
+pub fn split_at_midnights(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tz: Tz,
+) -> Vec<(NaiveDate, DateTime<Utc>, DateTime<Utc>)> {
+    let mut pieces = Vec::new();
+    let mut cursor = start;
+
+    while cursor < end {
+        let date = local_date(cursor, tz);
+        let next_day = date.succ_opt().map_or(end, |next| start_of_day(next, tz));
+        let piece_end = next_day.min(end).max(cursor + Duration::seconds(1));
+        pieces.push((date, cursor, piece_end.min(end)));
+        cursor = piece_end;
+    }
+
+    pieces
+}
+
+/// `local_date(start_time, timezone)` for SQL: the "YYYY-MM-DD" day an RFC3339
+/// instant falls on in the given IANA zone (the system zone when NULL).
+pub fn register_sql_functions(conn: &Connection) -> rusqlite::Result<()> {
+    let fallback = system_timezone();
+
+    conn.create_scalar_function(
+        "local_date",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            let Some(instant) = ctx.get::<Option<String>>(0)? else {
+                return Ok(None);
+            };
+            let Ok(instant) = instant.parse::<DateTime<Utc>>() else {
+                return Ok(None);
+            };
+            let zone: Option<String> = ctx.get(1)?;
+            let tz = session_timezone(zone.as_deref(), fallback);
+            Ok(Some(local_date(instant, tz).format("%Y-%m-%d").to_string()))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn session_within_a_day_is_one_piece() {
+        let pieces = split_at_midnights(utc("2024-01-15T09:00:00Z"), utc("2024-01-15T17:00:00Z"), Tz::Europe__Madrid);
+        assert_eq!(pieces, vec![(date(2024, 1, 15), utc("2024-01-15T09:00:00Z"), utc("2024-01-15T17:00:00Z"))]);
+    }
+
+    #[test]
+    fn cuts_at_local_midnight_not_utc() {
+        // 22:00 to 02:00 in Madrid, which is UTC+1 in winter
+        let pieces = split_at_midnights(utc("2024-01-15T21:00:00Z"), utc("2024-01-16T01:00:00Z"), Tz::Europe__Madrid);
+        assert_eq!(
+            pieces,
+            vec![
+                (date(2024, 1, 15), utc("2024-01-15T21:00:00Z"), utc("2024-01-15T23:00:00Z")),
+                (date(2024, 1, 16), utc("2024-01-15T23:00:00Z"), utc("2024-01-16T01:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn dst_day_is_shorter() {
+        // New York springs forward on 2024-03-10, so that day lasts 23 hours
+        let pieces = split_at_midnights(utc("2024-03-10T04:00:00Z"), utc("2024-03-11T05:00:00Z"), Tz::America__New_York);
+        let lengths: Vec<_> = pieces.iter().map(|(date, start, end)| (*date, (*end - *start).num_hours())).collect();
+        assert_eq!(lengths, vec![(date(2024, 3, 9), 1), (date(2024, 3, 10), 23), (date(2024, 3, 11), 1)]);
+    }
+
+    #[test]
+    fn day_without_a_midnight_starts_at_the_first_local_time() {
+        // Santiago jumps from 23:59:59 to 01:00 on 2024-09-08
+        assert_eq!(start_of_day(date(2024, 9, 8), Tz::America__Santiago), utc("2024-09-08T04:00:00Z"));
+
+        let pieces = split_at_midnights(utc("2024-09-08T02:00:00Z"), utc("2024-09-08T06:00:00Z"), Tz::America__Santiago);
+        assert_eq!(pieces.iter().map(|piece| piece.0).collect::<Vec<_>>(), vec![date(2024, 9, 7), date(2024, 9, 8)]);
+        assert_eq!(pieces[0].2, pieces[1].1);
+    }
+
+    #[test]
+    fn empty_span_has_no_pieces() {
+        let at = utc("2024-01-15T09:00:00Z");
+        assert!(split_at_midnights(at, at, Tz::UTC).is_empty());
+    }
+}
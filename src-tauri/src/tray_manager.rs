@@ -1,16 +1,20 @@
+use crate::commands;
 use crate::models::{Project, TimeSession};
 use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder, PredefinedMenuItem};
-use tauri::{AppHandle, Runtime};
+use tauri::{AppHandle, Manager, Runtime};
+use rusqlite::Connection;
 use std::collections::HashMap;
+use std::sync::Mutex;
 use chrono::Utc;
 
-fn format_duration(start_time: &chrono::DateTime<Utc>) -> String {
-    let now = Utc::now();
-    let duration = now.signed_duration_since(*start_time);
+/// Time the session has run so far, pauses left out
+fn format_duration(session: &TimeSession) -> String {
+    let until = session.paused_at.unwrap_or_else(Utc::now);
+    let total = ((until - session.start_time).num_seconds() - session.paused_seconds).max(0);
 
-    let hours = duration.num_hours();
-    let minutes = duration.num_minutes() % 60;
-    let seconds = duration.num_seconds() % 60;
+    let hours = total / 3600;
+    let minutes = total / 60 % 60;
+    let seconds = total % 60;
 
     if hours > 0 {
         format!("{}h {}m {}s", hours, minutes, seconds)
@@ -34,6 +38,17 @@ pub fn build_tray_menu<R: Runtime>(
 
     let mut menu_builder = MenuBuilder::new(app);
 
+    // A pending reminder's action goes first, since notifications cannot carry it
+    if let Some(reminder) = commands::reminders::pending_reminder(app) {
+        let label = match reminder.kind.as_str() {
+            "stop" => format!("⏰ Detener {}", reminder.project_name),
+            _ => format!("⏰ Iniciar {}", reminder.project_name),
+        };
+        let reminder_item = MenuItemBuilder::with_id("reminder_action", &label).build(app)?;
+        menu_builder = menu_builder.item(&reminder_item);
+        menu_builder = menu_builder.item(&PredefinedMenuItem::separator(app)?);
+    }
+
     // Show running sessions at the top, sorted A-Z by project name
     if running_sessions.is_empty() {
         let no_active = MenuItemBuilder::with_id("no_active", "No hay proyectos activos")
@@ -48,8 +63,12 @@ pub fn build_tray_menu<R: Runtime>(
         running_with_projects.sort_by(|a, b| a.1.name.to_lowercase().cmp(&b.1.name.to_lowercase()));
 
         for (session, project) in &running_with_projects {
-            let duration = format_duration(&session.start_time);
-            let display_text = format!("▶ {} - {}", project.name, duration);
+            let duration = format_duration(session);
+            let display_text = if session.paused_at.is_some() {
+                format!("⏸ {} - {} (en pausa)", project.name, duration)
+            } else {
+                format!("▶ {} - {}", project.name, duration)
+            };
             let item_id = format!("project_{}", project.id);
 
             let active_item = MenuItemBuilder::with_id(&item_id, &display_text)
@@ -108,3 +127,14 @@ pub fn update_tray_menu<R: Runtime>(
     tray.set_menu(Some(menu))?;
     Ok(())
 }
+
+/// Rebuild the tray menu from the database, for changes made outside the window
+pub fn refresh_tray_menu<R: Runtime>(app: &AppHandle<R>) {
+    let db = app.state::<Mutex<Connection>>();
+    if let (Ok(projects), Ok(running)) = (
+        commands::projects::get_all_projects(db.clone()),
+        commands::sessions::get_running_sessions(db.clone()),
+    ) {
+        let _ = update_tray_menu(app, projects, running);
+    }
+}
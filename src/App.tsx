@@ -4,6 +4,7 @@ import { listen } from '@tauri-apps/api/event';
 import { useStore } from './store/useStore';
 import { useTauriCommands } from './hooks/useTauriCommands';
 import Layout from './components/Layout';
+import IdlePrompt from './components/IdlePrompt';
 import Dashboard from './views/Dashboard';
 import Projects from './views/Projects';
 import Stats from './views/Stats';
@@ -99,6 +100,7 @@ function App() {
           <Route path="*" element={<Navigate to="/" replace />} />
         </Routes>
       </Layout>
+      <IdlePrompt />
     </BrowserRouter>
   );
 }
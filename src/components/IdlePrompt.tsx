@@ -0,0 +1,118 @@
+import { useState, useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { Moon } from 'lucide-react';
+import { useStore } from '../store/useStore';
+import { useTauriCommands } from '../hooks/useTauriCommands';
+import type { IdlePeriod, IdleSessionSpan } from '../types';
+import { formatDuration } from '../utils/formatTime';
+
+// Asks what to do with the time the backend saw the user away from the computer,
+// one running session at a time
+const IdlePrompt = () => {
+  const { projects, setSessions, setRunningSessions } = useStore();
+  const tauri = useTauriCommands();
+  const [pending, setPending] = useState<IdleSessionSpan[]>([]);
+  const [reassignTo, setReassignTo] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen<IdlePeriod>('idle-period-detected', (event) => {
+      setPending((current) => [...current, ...event.payload.sessions]);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const span = pending[0];
+  if (!span) {
+    return null;
+  }
+
+  const project = projects.find((p) => p.id === span.project_id);
+  const otherProjects = projects.filter((p) => p.status === 'active' && p.id !== span.project_id);
+  const formatClock = (value: string) =>
+    new Date(value).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' });
+
+  const resolve = async (action: 'keep' | 'discard' | 'reassign') => {
+    try {
+      await tauri.sessions.resolveIdleTime({
+        sessionId: span.session_id,
+        idleStart: span.idle_start,
+        idleEnd: span.idle_end,
+        action,
+        projectId: action === 'reassign' ? reassignTo : undefined,
+      });
+      const [sessions, runningSessions] = await Promise.all([
+        tauri.sessions.getAll(),
+        tauri.sessions.getRunning(),
+      ]);
+      setSessions(sessions);
+      setRunningSessions(runningSessions);
+      setError(null);
+      setReassignTo('');
+      setPending((current) => current.slice(1));
+    } catch (err) {
+      console.error('Error resolving idle time:', err);
+      setError(String(err));
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50">
+      <div className="bg-white rounded-lg p-6 w-full max-w-md">
+        <div className="flex items-center gap-3 mb-4">
+          <Moon size={24} className="text-blue-600" />
+          <h2 className="text-2xl font-bold">You were away</h2>
+        </div>
+        <p className="text-gray-700 mb-4">
+          <span className="font-medium">{project?.name ?? 'A session'}</span> kept running for{' '}
+          {formatDuration(span.idle_seconds)} while you were away ({formatClock(span.idle_start)}
+          {' – '}
+          {formatClock(span.idle_end)}).
+        </p>
+
+        {error && <p className="text-sm text-red-600 mb-4">{error}</p>}
+
+        <div className="space-y-3">
+          <button
+            onClick={() => resolve('keep')}
+            className="w-full px-4 py-2 border border-gray-300 rounded-lg hover:bg-gray-50"
+          >
+            Keep the time
+          </button>
+          <button
+            onClick={() => resolve('discard')}
+            className="w-full px-4 py-2 bg-red-600 text-white rounded-lg hover:bg-red-700"
+          >
+            Discard the time
+          </button>
+          <div className="flex gap-2">
+            <select
+              value={reassignTo}
+              onChange={(e) => setReassignTo(e.target.value)}
+              className="flex-1 px-3 py-2 border border-gray-300 rounded-lg"
+            >
+              <option value="">Move to project…</option>
+              {otherProjects.map((p) => (
+                <option key={p.id} value={p.id}>
+                  {p.name}
+                </option>
+              ))}
+            </select>
+            <button
+              onClick={() => resolve('reassign')}
+              disabled={!reassignTo}
+              className="px-4 py-2 bg-blue-600 text-white rounded-lg hover:bg-blue-700 disabled:opacity-50"
+            >
+              Reassign
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default IdlePrompt;
@@ -104,6 +104,20 @@ export const useTauriCommands = () => {
         }),
       stopAllRunning: () => invoke<TimeSession[]>('stop_all_running_sessions'),
       delete: (sessionId: string) => invoke<void>('delete_session', { sessionId }),
+      resolveIdleTime: (data: {
+        sessionId: string;
+        idleStart: string;
+        idleEnd: string;
+        action: 'keep' | 'discard' | 'reassign';
+        projectId?: string;
+      }) =>
+        invoke<TimeSession[]>('resolve_idle_time', {
+          sessionId: data.sessionId,
+          idleStart: data.idleStart,
+          idleEnd: data.idleEnd,
+          action: data.action,
+          projectId: data.projectId ?? null,
+        }),
     },
 
     stats: {
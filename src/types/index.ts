@@ -3,10 +3,46 @@ export interface Client {
   name: string;
   description?: string;
   color?: string;
+  hourly_rate?: number;
+  currency?: string;
+  billing_address?: string;
+  tax_id?: string;
   created_at: string;
   updated_at: string;
 }
 
+export interface Invoice {
+  id: string;
+  number: string;
+  client_id: string;
+  period_start: string; // YYYY-MM-DD
+  period_end: string; // YYYY-MM-DD
+  issue_date: string; // YYYY-MM-DD
+  currency?: string;
+  subtotal: number;
+  tax_rate: number; // percentage, e.g. 21.0
+  tax_amount: number;
+  total: number;
+  notes?: string;
+  created_at: string;
+}
+
+export interface InvoiceItem {
+  id: string;
+  invoice_id: string;
+  project_id?: string;
+  description: string;
+  hours: number;
+  rate: number;
+  amount: number;
+}
+
+export interface InvoiceDetail {
+  invoice: Invoice;
+  items: InvoiceItem[];
+  session_count: number;
+}
+
 export interface Project {
   id: string;
   name: string;
@@ -19,6 +55,8 @@ export interface Project {
   hours_per_day?: number;
   hours_per_week?: number;
   deadline?: string;
+  hourly_rate?: number; // overrides the client's rate when set
+  currency?: string;
   created_at: string;
   updated_at: string;
 }
@@ -31,6 +69,40 @@ export interface TimeSession {
   duration_seconds?: number;
   notes?: string;
   is_running: boolean;
+  billable: boolean;
+  invoice_id?: string; // set once invoiced; the session is then locked
+  task_id?: string;
+  timezone?: string; // IANA zone the session was recorded in
+  pomodoros: number;
+  paused_at?: string; // set while a running session is paused
+  paused_seconds: number; // time spent in finished pauses
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SessionPause {
+  id: string;
+  session_id: string;
+  paused_at: string;
+  resumed_at?: string;
+  reason: 'manual' | 'screen_lock' | 'merge_gap';
+}
+
+export interface Tag {
+  id: string;
+  name: string;
+  color?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface Task {
+  id: string;
+  project_id: string;
+  name: string;
+  description?: string;
+  status: 'todo' | 'in_progress' | 'done';
+  estimated_hours?: number;
   created_at: string;
   updated_at: string;
 }
@@ -40,6 +112,7 @@ export interface ProjectStats {
   total_seconds: number;
   total_hours: number;
   session_count: number;
+  running_seconds: number;
 }
 
 export interface DailyStats {
@@ -47,6 +120,7 @@ export interface DailyStats {
   total_seconds: number;
   total_hours: number;
   project_breakdown: ProjectTimeBreakdown[];
+  running_seconds: number;
 }
 
 export interface ProjectTimeBreakdown {
@@ -57,6 +131,190 @@ export interface ProjectTimeBreakdown {
   total_hours: number;
 }
 
+export interface ProjectBillingBreakdown extends ProjectTimeBreakdown {
+  client_id?: string;
+  billable_seconds: number;
+  billable_hours: number;
+  hourly_rate?: number; // the project's rate, or its client's
+  currency?: string;
+  amount?: number; // billable hours times the rate, when there is one
+}
+
+export interface ClientTimeBreakdown {
+  client_id?: string;
+  client_name?: string;
+  total_seconds: number;
+  total_hours: number;
+}
+
+export interface TaskTimeBreakdown {
+  project_id: string;
+  project_name: string;
+  task_id?: string; // empty for sessions without a task
+  task_name?: string;
+  status?: Task['status'];
+  estimated_hours?: number;
+  total_seconds: number;
+  total_hours: number;
+  session_count: number;
+}
+
+export interface TagTimeBreakdown {
+  tag_id: string;
+  tag_name: string;
+  color?: string;
+  total_seconds: number;
+  total_hours: number;
+  session_count: number;
+}
+
+export interface PeriodStats {
+  period: string;
+  start_date: string;
+  end_date: string;
+  total_seconds: number;
+  total_hours: number;
+  session_count: number;
+  project_breakdown: ProjectTimeBreakdown[];
+  client_breakdown: ClientTimeBreakdown[];
+  previous_total_seconds: number;
+  previous_total_hours: number;
+  change_percent?: number;
+}
+
+export interface BudgetStatus {
+  project_id: string;
+  project_name: string;
+  estimated_hours?: number;
+  consumed_seconds: number;
+  consumed_hours: number;
+  remaining_hours?: number;
+  percent_used?: number;
+  velocity_hours_per_day: number;
+  projected_completion_date?: string;
+  deadline?: string;
+  on_track?: boolean;
+}
+
+export interface BurnDownPoint {
+  date: string;
+  consumed_hours: number;
+  remaining_hours?: number;
+}
+
+export interface TargetProgress {
+  project_id: string;
+  project_name: string;
+  daily_target_hours?: number;
+  today_hours: number;
+  today_remaining_hours?: number;
+  today_percent?: number;
+  daily_streak: number;
+  weekly_target_hours?: number;
+  week_start_date: string;
+  week_hours: number;
+  week_remaining_hours?: number;
+  week_percent?: number;
+  weekly_streak: number;
+}
+
+export interface PomodoroConfig {
+  focus_minutes?: number;
+  short_break_minutes?: number;
+  long_break_minutes?: number;
+  cycles_before_long_break?: number;
+}
+
+export interface PomodoroStatus {
+  project_id: string;
+  task_id?: string;
+  phase: 'focus' | 'short_break' | 'long_break';
+  phase_ends_at: string;
+  remaining_seconds: number;
+  completed_pomodoros: number;
+  session_id?: string;
+}
+
+export interface Reminder {
+  kind: 'start' | 'stop';
+  project_id: string;
+  project_name: string;
+  task_id?: string;
+  session_id?: string;
+  created_at: string;
+}
+
+export interface ManualSessionEntry {
+  project_id: string;
+  task_id?: string;
+  start_time: string;
+  end_time: string;
+  notes?: string;
+  billable?: boolean;
+  tag_ids?: string[];
+}
+
+export interface BulkSessionReport {
+  created: TimeSession[];
+  errors: { index: number; message: string }[];
+}
+
+export interface SessionOverlap {
+  first: TimeSession;
+  second: TimeSession;
+  overlap_seconds: number;
+}
+
+export interface JournalEntry {
+  id: number;
+  operation: 'delete_session' | 'delete_project' | 'delete_client';
+  target_id: string;
+  label: string;
+  row_count: number; // rows the operation removed or changed
+  undone: boolean;
+  created_at: string;
+  purge_at: string;
+}
+
+export interface EntityCounts {
+  total: number;
+  new: number; // ids not present in the database
+  existing: number; // ids already present
+}
+
+export interface BackupPreview {
+  format_version: number;
+  schema_version: number;
+  created_at: string;
+  clients: EntityCounts;
+  projects: EntityCounts;
+  tasks: EntityCounts;
+  sessions: EntityCounts;
+  tags: EntityCounts;
+  invoices: EntityCounts;
+  settings: number;
+  invoice_number_conflicts: string[]; // skipped on merge
+}
+
+export interface IdleSessionSpan {
+  session_id: string;
+  project_id: string;
+  idle_start: string;
+  idle_end: string;
+  idle_seconds: number;
+}
+
+export interface IdlePeriod {
+  idle_start: string;
+  idle_end: string;
+  sessions: IdleSessionSpan[]; // the running sessions the period overlapped
+}
+
+export interface OrphanedSession {
+  session: TimeSession; // still marked running after an unclean exit
+  last_heartbeat?: string;
+}
+
 export const PRIORITY_LABELS: Record<number, string> = {
   1: 'Low',
   2: 'Medium',